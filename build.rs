@@ -0,0 +1,5 @@
+// 练习是通过 include_dir 嵌入到 clearning 中的, 练习修改之后需要重新编译
+fn main() {
+    println!("cargo:rerun-if-changed=exercises");
+    println!("cargo:rerun-if-changed=harness");
+}
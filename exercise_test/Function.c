@@ -0,0 +1,10 @@
+#include <stdio.h>
+// exercise for test
+
+int add(int a, int b) {
+    return a + b;
+}
+
+int main() {
+    printf("add(1, 2) = %d", add(1, 2));
+}
@@ -0,0 +1,9 @@
+#include "../harness/clearning_test.h"
+
+int add(int a, int b);
+
+int main() {
+    CHECK(add(1, 2) == 3, "add(1, 2) == 3");
+    CHECK(add(1, 2) == 4, "add(1, 2) == 4");
+    return 0;
+}
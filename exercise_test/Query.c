@@ -0,0 +1,19 @@
+#include <stdio.h>
+// exercise for test
+
+struct point { int x; int y; };
+
+int counter = 0;
+extern int shared;
+
+int add(int a, long b) {
+    return a + b;
+}
+
+void show(const char *s) {
+    printf("%s", s);
+}
+
+int main() {
+    show("Hello World!");
+}
@@ -0,0 +1,6 @@
+#include <stdio.h>
+// exercise for test
+
+int main() {
+    printf("%d", answer);
+}
@@ -0,0 +1,6 @@
+#include "greet.h"
+// exercise for test
+
+int main() {
+    greet("World");
+}
@@ -0,0 +1,23 @@
+// 编译器报错并不可怕, 学会阅读错误信息, 是写好 C 语言的第一步
+// 这次练习和以往不同: 你要写出 *不能* 通过编译的代码
+
+/*
+    C 语言中, 标识符在使用之前必须先声明。
+    如果使用了一个没有声明过的变量, gcc 会给出类似下面的错误:
+        error: 'x' undeclared (first use in this function)
+*/
+
+// 练习: 在 main 函数中使用一个没有声明过的变量, 让 gcc 报出上面的错误
+// 注意: 只有产生了这个错误才算通过, 其他的编译错误是不行的
+// 在终端输入 hint 获取提示
+
+// I AM NOT DONE
+
+#include <stdio.h>
+
+int main() {
+    int declared = 1;
+    printf("declared = %d\n", declared);
+
+    return 0;
+}
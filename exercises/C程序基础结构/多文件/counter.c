@@ -0,0 +1,11 @@
+#include "counter.h"
+
+int count = 0;
+
+void increase(void) {
+    count = count + 1;
+}
+
+int current(void) {
+    return count;
+}
@@ -0,0 +1,10 @@
+#ifndef COUNTER_H
+#define COUNTER_H
+
+// 让计数器加一
+void increase(void);
+
+// 返回计数器当前的值
+int current(void);
+
+#endif
@@ -0,0 +1,25 @@
+// 一个 C 程序可以由多个源文件组成, 每个源文件被单独编译, 最后再链接到一起
+// 这次练习由三个文件组成:
+//     main.c              (就是这个文件)
+//     counter.c           计数器的实现
+//     include/counter.h   计数器的声明, 让 main.c 知道有哪些函数可以用
+//
+// 现在这个程序无法通过链接, 请阅读报错信息, 找出问题所在
+// 提示: 两个源文件都定义了名为 count 的全局变量
+//       static 可以让全局变量只在它所在的文件中可见
+
+// I AM NOT DONE
+
+#include <stdio.h>
+#include "counter.h"
+
+int count = 100;
+
+int main() {
+    increase();
+    increase();
+    increase();
+    printf("计数器的值: %d\n", current());
+    printf("main.c 中的 count: %d\n", count);
+    return 0;
+}
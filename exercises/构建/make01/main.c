@@ -0,0 +1,6 @@
+#include "greet.h"
+
+int main() {
+    greet("Makefile");
+    return 0;
+}
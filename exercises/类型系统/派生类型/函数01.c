@@ -0,0 +1,29 @@
+// 函数可以把一段经常使用的代码包装起来, 需要时只要调用它就可以了
+// 一个函数由 返回值类型、函数名、参数列表 和 函数体 组成, 例如:
+//
+//     int square(int x) {
+//         return x * x;
+//     }
+//
+// 请补全下面的 add 和 max 函数:
+//     add 返回两个参数的和
+//     max 返回两个参数中较大的那一个
+// clearning 会悄悄地调用你写的函数, 检查它们的返回值是否正确
+
+// I AM NOT DONE
+
+#include <stdio.h>
+
+int add(int a, int b) {
+
+}
+
+int max(int a, int b) {
+
+}
+
+int main() {
+    printf("add(1, 2) = %d\n", add(1, 2));
+    printf("max(3, 7) = %d\n", max(3, 7));
+    return 0;
+}
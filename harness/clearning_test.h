@@ -0,0 +1,18 @@
+// clearning 隐藏测试用的头文件
+// 测试代码会和学生的练习文件链接在一起, 学生的 main 函数会被改名为 clearning_student_main
+// 每一条 CHECK 的结果都会按照 "[clearning] PASS/FAIL 描述" 的格式输出, 由 clearning 解析
+
+#ifndef CLEARNING_TEST_H
+#define CLEARNING_TEST_H
+
+#include <stdio.h>
+
+int clearning_student_main(void);
+
+#define CHECK(cond, desc)                                                   \
+    do {                                                                    \
+        printf("[clearning] %s %s\n", (cond) ? "PASS" : "FAIL", (desc));   \
+        fflush(stdout);                                                     \
+    } while (0)
+
+#endif
@@ -0,0 +1,12 @@
+#include "clearning_test.h"
+
+int add(int a, int b);
+int max(int a, int b);
+
+int main() {
+    CHECK(add(1, 2) == 3, "add(1, 2) 应该等于 3");
+    CHECK(add(-5, 5) == 0, "add(-5, 5) 应该等于 0");
+    CHECK(max(3, 7) == 7, "max(3, 7) 应该等于 7");
+    CHECK(max(-1, -9) == -1, "max(-1, -9) 应该等于 -1");
+    return 0;
+}
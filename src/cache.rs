@@ -0,0 +1,136 @@
+use crate::config;
+use crate::course;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+// 编译缓存所在的文件夹, 每个 key 一个子文件夹
+const CACHE_DIR: &str = ".clearning/cache";
+// 缓存中的可执行文件
+const BINARY: &str = "binary";
+// 编译时编译器给出的警告
+const WARNINGS: &str = "warnings.txt";
+// 练习已经通过了检验, 内容是当时 info.toml 的哈希
+const PASSED: &str = "passed";
+
+// 编译器的版本, 换了编译器之后之前的缓存都不再使用
+fn compiler_version() -> &'static str {
+    static VERSION: OnceLock<String> = OnceLock::new();
+    VERSION.get_or_init(|| {
+        Command::new(config::get().compiler())
+            .arg("--version")
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default()
+    })
+}
+
+// info.toml 的哈希, 检查规则、预期错误等改变之后, 之前通过的结果不再算数
+fn manifest_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| {
+        let manifest = fs::read(course::MANIFEST).unwrap_or_default();
+        format!("{:x}", Sha256::digest(&manifest))
+    })
+}
+
+// 根据编译器版本、编译参数和所有输入文件的内容计算缓存的 key
+// 文件夹会递归地加入其中的所有文件
+pub fn key(options: &str, inputs: &[&Path]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(compiler_version());
+    hasher.update([0]);
+    hasher.update(options);
+    let mut files = Vec::new();
+    for input in inputs {
+        collect_files(input, &mut files);
+    }
+    for file in files {
+        hasher.update([0]);
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(fs::read(&file).unwrap_or_default());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return;
+    }
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+        .unwrap_or_default();
+    entries.sort();
+    for entry in entries {
+        collect_files(&entry, files);
+    }
+}
+
+fn entry(key: &str) -> PathBuf {
+    Path::new(CACHE_DIR).join(key)
+}
+
+// 把缓存的可执行文件复制到 binary, 返回编译时的警告; 没有缓存时返回 None
+pub fn load(key: &str, binary: &Path) -> Option<String> {
+    let entry = entry(key);
+    let warnings = fs::read_to_string(entry.join(WARNINGS)).ok()?;
+    fs::copy(entry.join(BINARY), binary).ok()?;
+    Some(warnings)
+}
+
+// 保存编译出的可执行文件, 缓存只是为了加快速度, 保存失败时直接忽略
+pub fn store(key: &str, binary: &Path, warnings: &str) {
+    let entry = entry(key);
+    let _ = fs::create_dir_all(&entry)
+        .and_then(|_| fs::copy(binary, entry.join(BINARY)))
+        .and_then(|_| fs::write(entry.join(WARNINGS), warnings));
+}
+
+// 这个 key 对应的代码之前是否通过了检验
+pub fn passed(key: &str) -> bool {
+    fs::read_to_string(entry(key).join(PASSED)).is_ok_and(|hash| hash == manifest_hash())
+}
+
+pub fn mark_passed(key: &str) {
+    let entry = entry(key);
+    let _ = fs::create_dir_all(&entry).and_then(|_| fs::write(entry.join(PASSED), manifest_hash()));
+}
+
+// cache clean: 删除所有的编译缓存
+pub fn clean() -> Result<(), ()> {
+    match fs::remove_dir_all(CACHE_DIR) {
+        Ok(_) => {
+            success!("{}", t!("cache.cleaned", CACHE_DIR));
+            Ok(())
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            success!("{}", t!("cache.empty", CACHE_DIR));
+            Ok(())
+        }
+        Err(e) => {
+            warn!("{}", t!("cache.clean_failed", e));
+            Err(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试 key 只在编译参数或者文件内容改变时改变
+    #[test]
+    fn test_key() {
+        let files = [Path::new("exercise_test/HelloWorld.c")];
+        let key1 = key("-Wall", &files);
+        assert_eq!(key1, key("-Wall", &files));
+        assert_ne!(key1, key("-O2", &files));
+        assert_ne!(key1, key("-Wall", &[Path::new("exercise_test/Sum.c")]));
+        assert_ne!(key1, key("-Wall", &[Path::new("exercise_test/multi")]));
+    }
+}
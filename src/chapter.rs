@@ -0,0 +1,160 @@
+use crate::exercise::Exercise;
+use crate::graph;
+use crate::ui::emoji;
+use console::style;
+use std::collections::HashSet;
+
+// 一个章节, 以及按照 info.toml 中的顺序排列的练习
+pub struct Chapter<'a> {
+    pub name: &'a str,
+    pub exercises: Vec<&'a Exercise>,
+}
+
+// 把练习按照章节分组, 章节的顺序是它的第一个练习在 info.toml 中出现的顺序
+pub fn chapters<'a>(exercises: impl IntoIterator<Item = &'a Exercise>) -> Vec<Chapter<'a>> {
+    let mut chapters: Vec<Chapter> = Vec::new();
+    for exercise in exercises {
+        let name = exercise.chapter_name();
+        match chapters.iter_mut().find(|chapter| chapter.name == name) {
+            Some(chapter) => chapter.exercises.push(exercise),
+            None => chapters.push(Chapter {
+                name,
+                exercises: vec![exercise],
+            }),
+        }
+    }
+    chapters
+}
+
+pub fn has_tag(exercise: &Exercise, tag: &str) -> bool {
+    exercise.tags.iter().any(|t| t == tag)
+}
+
+// 只保留带有这个标签的练习, 没有这样的练习时退出
+pub fn retain_tag(exercises: &mut Vec<Exercise>, tag: &str) {
+    exercises.retain(|e| has_tag(e, tag));
+    if exercises.is_empty() {
+        no_tagged(tag);
+    }
+}
+
+pub fn no_tagged(tag: &str) -> ! {
+    println!("{}", t!("chapter.no_tag", tag));
+    std::process::exit(1);
+}
+
+// 按照难度或者预计时间排序, 不分章节地列出练习
+// done 是根据全部练习计算的, 这样筛选掉的前置练习不会影响解锁状态
+pub fn list_sorted(exercises: &[&Exercise], done: &HashSet<&str>, key: &str) {
+    let mut sorted = exercises.to_vec();
+    match key {
+        "difficulty" => sorted.sort_by_key(|e| e.difficulty),
+        "time" => sorted.sort_by_key(|e| e.estimated_minutes),
+        _ => sorted.sort_by(|a, b| a.name.cmp(&b.name)),
+    }
+    for exercise in sorted {
+        let mark = if done.contains(exercise.id()) {
+            style("✓").green()
+        } else {
+            style("·").dim()
+        };
+        println!(
+            "{} {} {} {}",
+            mark,
+            exercise.name,
+            style(exercise.metadata()).dim(),
+            style(exercise.tags.join(", ")).blue()
+        );
+    }
+}
+
+// 只保留一个章节中的练习, 找不到这个章节时列出所有的章节并退出
+pub fn retain(exercises: &mut Vec<Exercise>, chapter: &str) {
+    if !exercises.iter().any(|e| e.chapter_name() == chapter) {
+        println!("{}", t!("chapter.not_found", chapter));
+        for chapter in chapters(exercises.iter()) {
+            println!("  {}", chapter.name);
+        }
+        std::process::exit(1);
+    }
+    exercises.retain(|e| e.chapter_name() == chapter);
+}
+
+// 按照章节和小节列出所有的练习, 以及每一章的进度
+// 还没有解锁的练习会标出需要先完成的练习
+// shown 是要列出的练习, all 是全部练习, 用来找出前置练习
+pub fn list(shown: &[&Exercise], all: &[Exercise], done: &HashSet<&str>) {
+    for chapter in chapters(shown.iter().copied()) {
+        let finished = chapter
+            .exercises
+            .iter()
+            .filter(|e| done.contains(e.id()))
+            .count();
+        println!(
+            "{} {}",
+            style(chapter.name).bold(),
+            style(format!("({}/{})", finished, chapter.exercises.len())).blue()
+        );
+        let mut section = None;
+        for exercise in &chapter.exercises {
+            if exercise.section_name() != section {
+                section = exercise.section_name();
+                if let Some(section) = section {
+                    println!("  {}", style(section).dim());
+                }
+            }
+            let indent = if section.is_some() { "    " } else { "  " };
+            let missing = graph::missing(exercise, all, done);
+            if done.contains(exercise.id()) {
+                println!(
+                    "{}{} {} {}",
+                    indent,
+                    style("✓").green(),
+                    exercise.name,
+                    style(exercise.metadata()).dim()
+                );
+            } else if missing.is_empty() {
+                println!(
+                    "{}{} {} {}",
+                    indent,
+                    style("·").dim(),
+                    exercise.name,
+                    style(exercise.metadata()).dim()
+                );
+            } else {
+                println!(
+                    "{}{} {} {}",
+                    indent,
+                    emoji("🔒", "x"),
+                    style(&exercise.name).dim(),
+                    style(t!("chapter.requires", missing.join(", "))).dim()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::PathBuf;
+
+    // 测试章节按照第一次出现的顺序分组
+    #[test]
+    fn test_chapters() {
+        let exercise = |path: &str| Exercise {
+            path: PathBuf::from(path),
+            ..Default::default()
+        };
+        let exercises = vec![
+            exercise("exercises/C程序基础结构/main函数.c"),
+            exercise("exercises/类型系统/基础类型/整型/int01.c"),
+            exercise("exercises/C程序基础结构/声明/标识符.c"),
+        ];
+        let chapters = chapters(&exercises);
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].name, "C程序基础结构");
+        assert_eq!(chapters[0].exercises.len(), 2);
+        assert_eq!(chapters[1].name, "类型系统");
+    }
+}
@@ -0,0 +1,254 @@
+use console::style;
+use regex::Regex;
+use serde::Deserialize;
+
+// 以这个前缀开头的规则会被当作正则表达式, 否则当作普通的记号
+pub const REGEX_PREFIX: &str = "re:";
+
+// info.toml 中声明的静态代码检查
+// required/forbidden 检查去掉注释和字符串之后的代码
+// required_literals/forbidden_literals 只检查字符串字面量的内容
+#[derive(Deserialize, Default)]
+pub struct Checks {
+    // 代码中必须出现的记号, 例如 "const"
+    #[serde(default)]
+    pub required: Vec<String>,
+    // 代码中不允许出现的记号
+    #[serde(default)]
+    pub forbidden: Vec<String>,
+    // 字符串中必须出现的内容, 例如 printf 的格式说明符 "%d"
+    #[serde(default)]
+    pub required_literals: Vec<String>,
+    // 字符串中不允许出现的内容, 例如直接写出的答案
+    #[serde(default)]
+    pub forbidden_literals: Vec<String>,
+}
+
+// 一条没有通过的检查
+#[derive(PartialEq, Debug)]
+pub struct Violation {
+    // 给学生看的说明
+    pub message: String,
+    // 违规的行号 (从 1 开始), 缺少必须的内容时没有行号
+    pub line: Option<usize>,
+}
+
+impl Checks {
+    pub fn is_empty(&self) -> bool {
+        self.required.is_empty()
+            && self.forbidden.is_empty()
+            && self.required_literals.is_empty()
+            && self.forbidden_literals.is_empty()
+    }
+
+    // 检查源代码, 返回所有没有通过的规则
+    pub fn check(&self, source: &str) -> Vec<Violation> {
+        let stripped = strip(source);
+        let literals = stripped.literals.join("\n");
+        let mut violations = Vec::new();
+
+        for rule in &self.required {
+            if find(rule, &stripped.code).is_none() {
+                violations.push(Violation {
+                    message: t!("check.required", display(rule)),
+                    line: None,
+                });
+            }
+        }
+        for rule in &self.forbidden {
+            if let Some(offset) = find(rule, &stripped.code) {
+                violations.push(Violation {
+                    message: t!("check.forbidden", display(rule)),
+                    line: Some(line_of(&stripped.code, offset)),
+                });
+            }
+        }
+        for rule in &self.required_literals {
+            if find(rule, &literals).is_none() {
+                violations.push(Violation {
+                    message: t!("check.required_literal", display(rule)),
+                    line: None,
+                });
+            }
+        }
+        for rule in &self.forbidden_literals {
+            if let Some((index, _)) = stripped
+                .literals
+                .iter()
+                .enumerate()
+                .find(|(_, literal)| find(rule, literal).is_some())
+            {
+                violations.push(Violation {
+                    message: t!("check.forbidden_literal", display(rule)),
+                    line: Some(stripped.literal_lines[index]),
+                });
+            }
+        }
+        violations
+    }
+}
+
+// 输出每一条没有通过的检查
+pub fn print_violations(violations: &[Violation]) {
+    for violation in violations {
+        match violation.line {
+            Some(line) => println!(
+                "  {} {}",
+                style(t!("check.line", line)).blue().bold(),
+                style(&violation.message).red()
+            ),
+            None => println!("  {}", style(&violation.message).red()),
+        }
+    }
+}
+
+// 去掉注释, 并把字符串和字符字面量从代码中分离出来的结果
+#[derive(PartialEq, Debug)]
+pub struct Stripped {
+    // 去掉注释, 字符串和字符字面量只剩下一对引号的代码, 换行会被保留
+    pub code: String,
+    // 每一个字符串字面量的内容
+    pub literals: Vec<String>,
+    // 每一个字符串字面量所在的行号
+    pub literal_lines: Vec<usize>,
+}
+
+// 去掉 C 代码中的注释, 并分离出字符串字面量
+pub fn strip(source: &str) -> Stripped {
+    let mut code = String::new();
+    let mut literals = Vec::new();
+    let mut literal_lines = Vec::new();
+    let mut line = 1;
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                // 行注释, 一直到行尾
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        code.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                // 块注释, 换成一个空格, 保留其中的换行
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        code.push('\n');
+                    }
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+                code.push(' ');
+            }
+            '"' | '\'' => {
+                let quote = c;
+                let start_line = line;
+                let mut literal = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            literal.push(c);
+                            if let Some(escaped) = chars.next() {
+                                literal.push(escaped);
+                            }
+                        }
+                        '\n' => {
+                            // 没有闭合的字面量, 到行尾为止
+                            line += 1;
+                            code.push('\n');
+                            break;
+                        }
+                        c if c == quote => break,
+                        c => literal.push(c),
+                    }
+                }
+                code.push(quote);
+                code.push(quote);
+                if quote == '"' {
+                    literals.push(literal);
+                    literal_lines.push(start_line);
+                }
+            }
+            '\n' => {
+                line += 1;
+                code.push(c);
+            }
+            c => code.push(c),
+        }
+    }
+
+    Stripped {
+        code,
+        literals,
+        literal_lines,
+    }
+}
+
+// 在 text 中查找规则, 返回第一次出现的位置
+// 普通的标识符按照整个单词匹配, 例如 "int" 不会匹配到 "printf"
+fn find(rule: &str, text: &str) -> Option<usize> {
+    let pattern = match rule.strip_prefix(REGEX_PREFIX) {
+        Some(pattern) => pattern.to_string(),
+        None if rule.chars().all(|c| c.is_alphanumeric() || c == '_') => {
+            format!(r"\b{}\b", regex::escape(rule))
+        }
+        None => regex::escape(rule),
+    };
+    match Regex::new(&pattern) {
+        Ok(re) => re.find(text).map(|m| m.start()),
+        Err(_) => {
+            println!("{}", t!("check.bad_regex", rule));
+            None
+        }
+    }
+}
+
+// 给学生看的规则, 去掉正则表达式的前缀
+fn display(rule: &str) -> &str {
+    rule.strip_prefix(REGEX_PREFIX).unwrap_or(rule)
+}
+
+fn line_of(text: &str, offset: usize) -> usize {
+    text[..offset].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试注释被去掉, 字符串被分离出来
+    #[test]
+    fn test_strip() {
+        let source = "int a; // const\n/* union\n */ printf(\"%d \\\" const\", 'c');\n";
+        let stripped = strip(source);
+        assert_eq!(stripped.code, "int a; \n\n  printf(\"\", '');\n");
+        assert_eq!(stripped.literals, vec!["%d \\\" const".to_string()]);
+        assert_eq!(stripped.literal_lines, vec![3]);
+    }
+
+    // 测试四种规则
+    #[test]
+    fn test_check() {
+        let checks = Checks {
+            required: vec!["const".to_string(), "union".to_string()],
+            forbidden: vec!["re:printf\\s*\\(".to_string()],
+            required_literals: vec!["%d".to_string()],
+            forbidden_literals: vec!["30".to_string()],
+        };
+        let source = "// union\nconst int a = 30;\nint main() {\n    printf(\"%d\\n\", a);\n    printf(\"30\");\n}\n";
+        let violations = checks.check(source);
+        assert_eq!(violations.len(), 3);
+        assert!(violations[0].message.contains("union"));
+        assert_eq!(violations[1].line, Some(4));
+        assert_eq!(violations[2].line, Some(5));
+    }
+}
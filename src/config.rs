@@ -0,0 +1,261 @@
+use crate::course;
+use crate::i18n;
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+// 课程的配置文件, 和 info.toml 放在一起
+pub const COURSE_CONFIG: &str = "clearning.toml";
+
+// 可以配置的项目和说明在消息目录中的 key, list 按照这个顺序输出
+pub const KEYS: &[(&str, &str)] = &[
+    ("compiler", "config.key.compiler"),
+    ("flags", "config.key.flags"),
+    ("timeout", "config.key.timeout"),
+    ("lang", "config.key.lang"),
+    ("color", "config.key.color"),
+    ("emoji", "config.key.emoji"),
+    ("debounce", "config.key.debounce"),
+    ("auto_advance", "config.key.auto_advance"),
+];
+
+// 用户偏好, 没有配置的项目使用默认值
+#[derive(Deserialize, Serialize, Default, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiler: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debounce: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_advance: Option<bool>,
+}
+
+impl Config {
+    // 用 other 中配置了的项目覆盖自己的项目
+    fn merge(self, other: Config) -> Config {
+        Config {
+            compiler: other.compiler.or(self.compiler),
+            flags: other.flags.or(self.flags),
+            timeout: other.timeout.or(self.timeout),
+            lang: other.lang.or(self.lang),
+            color: other.color.or(self.color),
+            emoji: other.emoji.or(self.emoji),
+            debounce: other.debounce.or(self.debounce),
+            auto_advance: other.auto_advance.or(self.auto_advance),
+        }
+    }
+
+    pub fn compiler(&self) -> &str {
+        self.compiler.as_deref().unwrap_or("gcc")
+    }
+
+    pub fn flags(&self) -> &[String] {
+        self.flags.as_deref().unwrap_or(&[])
+    }
+
+    // 0 表示不限制运行时间
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+            .filter(|&seconds| seconds > 0)
+            .map(Duration::from_secs)
+    }
+
+    pub fn emoji(&self) -> bool {
+        self.emoji.unwrap_or(true)
+    }
+
+    pub fn debounce(&self) -> Duration {
+        Duration::from_millis(self.debounce.unwrap_or(2000))
+    }
+
+    pub fn auto_advance(&self) -> bool {
+        self.auto_advance.unwrap_or(false)
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+// 读取用户的配置和 root 中课程的配置, 课程的配置优先
+// 配置文件有错误时提示之后退出, 以免错误的配置被悄悄忽略
+pub fn init(root: &Path) {
+    let mut config = Config::default();
+    for path in user_path().into_iter().chain(Some(root.join(COURSE_CONFIG))) {
+        let path = path.as_path();
+        match read(path) {
+            Ok(Some(table)) => match table.try_into::<Config>() {
+                Ok(file) => config = config.merge(file),
+                Err(e) => {
+                    println!("{}", t!("config.bad_file", path.display(), e));
+                    std::process::exit(1);
+                }
+            },
+            Ok(None) => {}
+            Err(e) => {
+                println!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(color) = config.color {
+        console::set_colors_enabled(color);
+    }
+    let _ = CONFIG.set(config);
+}
+
+// 当前的配置, 没有调用过 init 时 (例如在测试中) 全部使用默认值
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+// 用户的配置文件 ~/.config/clearning/config.toml, Windows 下放在 %APPDATA%\clearning 中
+pub fn user_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(dir.join("clearning").join("config.toml"))
+}
+
+// 读取一个配置文件, 文件不存在时返回 None
+fn read(path: &Path) -> Result<Option<toml::Value>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(_) => return Ok(None),
+    };
+    text.parse::<toml::Value>()
+        .map(Some)
+        .map_err(|e| t!("config.bad_file", path.display(), e))
+}
+
+fn check_key(key: &str) -> Result<(), ()> {
+    if KEYS.iter().any(|(k, _)| *k == key) {
+        Ok(())
+    } else {
+        warn!("{}", t!("config.unknown_key", key));
+        Err(())
+    }
+}
+
+// 配置项当前的值, 没有配置时返回 None
+fn value_of(config: &Config, key: &str) -> Option<toml::Value> {
+    toml::Value::try_from(config)
+        .ok()
+        .and_then(|table| table.get(key).cloned())
+}
+
+// config get: 输出一个配置项的值
+pub fn print_value(key: &str) -> Result<(), ()> {
+    check_key(key)?;
+    match value_of(get(), key) {
+        Some(value) => println!("{}", value),
+        None => println!("{}", style(t!("config.unset")).dim()),
+    }
+    Ok(())
+}
+
+// config list: 输出所有配置项的值和说明
+pub fn list() {
+    for (key, description) in KEYS {
+        let value = match value_of(get(), key) {
+            Some(value) => value.to_string(),
+            None => style(t!("config.unset")).dim().to_string(),
+        };
+        println!("{} = {}", style(key).bold(), value);
+        println!("    {}", style(i18n::message(description)).dim());
+    }
+    println!();
+    println!("{}", t!("config.course_file", COURSE_CONFIG));
+    if let Some(path) = user_path() {
+        println!("{}", t!("config.user_file", path.display()));
+    }
+}
+
+// 把字符串解析成配置的值: 能解析成 toml 的值 (数字、true、数组等) 就按照 toml 解析, 否则当作字符串
+fn parse_value(value: &str) -> toml::Value {
+    format!("value = {}", value)
+        .parse::<toml::Value>()
+        .ok()
+        .and_then(|table| table.get("value").cloned())
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
+// config set: 修改 root 中的课程配置文件, global 为 true 时修改用户配置文件
+pub fn set(key: &str, value: &str, global: bool, root: &Path) -> Result<(), ()> {
+    check_key(key)?;
+    let path = if global {
+        user_path().ok_or_else(|| warn!("{}", t!("config.no_user_dir")))?
+    } else if root.join(course::MANIFEST).is_file() {
+        root.join(COURSE_CONFIG)
+    } else {
+        warn!("{}", t!("config.not_course", root.display()));
+        return Err(());
+    };
+    let mut table = match read(&path).map_err(|e| warn!("{}", e))? {
+        Some(toml::Value::Table(table)) => table,
+        _ => toml::value::Table::new(),
+    };
+    table.insert(key.to_string(), parse_value(value));
+
+    // 写入之前先检查类型, 例如 timeout 必须是数字
+    let table = toml::Value::Table(table);
+    if let Err(e) = table.clone().try_into::<Config>() {
+        warn!("{}", t!("config.bad_value", e));
+        return Err(());
+    }
+    let saved = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, toml::to_string(&table).unwrap()));
+    match saved {
+        Ok(_) => {
+            success!("{}", t!("config.written", path.display()));
+            Ok(())
+        }
+        Err(e) => {
+            warn!("{}", t!("config.write_failed", e));
+            Err(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试配置的合并和值的解析
+    #[test]
+    fn test_merge() {
+        let user: Config = toml::from_str("compiler = \"clang\"\ntimeout = 5").unwrap();
+        let course: Config = toml::from_str("timeout = 10\nflags = [\"-Wall\"]").unwrap();
+        let config = user.merge(course);
+        assert_eq!(config.compiler(), "clang");
+        assert_eq!(config.timeout(), Some(Duration::from_secs(10)));
+        assert_eq!(config.flags(), &["-Wall".to_string()]);
+        assert!(toml::from_str::<Config>("compilr = \"clang\"").is_err());
+
+        assert_eq!(parse_value("10"), toml::Value::Integer(10));
+        assert_eq!(parse_value("clang"), toml::Value::String("clang".into()));
+        assert_eq!(parse_value("false"), toml::Value::Boolean(false));
+
+        // 每个配置项的说明都要在消息目录中, 否则 list 时会 panic
+        for (_, description) in KEYS {
+            assert!(!i18n::message(description).is_empty());
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use crate::i18n::{self, Lang};
+use crate::map::COURSE_MAP;
+use crate::update;
+use console::style;
+use include_dir::{include_dir, Dir, DirEntry};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+// 课程的清单, 每个课程包中都必须有
+pub const MANIFEST: &str = "info.toml";
+// 不带子命令运行时输出的说明, 英文说明放在 default_out.en.txt 中
+const WELCOME: &str = "default_out.txt";
+const WELCOME_EN: &str = "default_out.en.txt";
+
+// 编译时嵌入的默认课程: 练习和讲义, 以及隐藏测试
+static EXERCISES: Dir = include_dir!("$CARGO_MANIFEST_DIR/exercises");
+static HARNESS: Dir = include_dir!("$CARGO_MANIFEST_DIR/harness");
+
+// 默认课程文件夹中的其他文件
+const FILES: &[(&str, &[u8])] = &[
+    (MANIFEST, include_bytes!("../info.toml")),
+    (WELCOME, include_bytes!("../default_out.txt")),
+    (WELCOME_EN, include_bytes!("../default_out.en.txt")),
+    (COURSE_MAP, include_bytes!("../C语言基础.xmind")),
+];
+
+// 复制课程文件夹时跳过的文件夹: 学习记录、git 仓库和 cargo 的构建结果
+const SKIPPED: &[&str] = &[".clearning", ".git", "target"];
+
+// 课程的来源
+#[derive(PartialEq, Debug)]
+pub enum Course {
+    // clearning 中嵌入的默认课程
+    Embedded,
+    // 一个课程文件夹, 可以直接在里面做练习
+    Dir(PathBuf),
+    // 一个 zip 压缩的课程包, 需要先用 init 解压
+    Archive(PathBuf),
+}
+
+impl Course {
+    // 根据 --course 参数找到课程, 文件就当作课程包, 文件夹就当作课程文件夹
+    pub fn from_arg(arg: Option<&str>) -> Result<Course, String> {
+        let path = match arg {
+            Some(path) => PathBuf::from(path),
+            None => return Ok(Course::Embedded),
+        };
+        if path.is_file() {
+            check_archive(&path)?;
+            Ok(Course::Archive(path))
+        } else if path.join(MANIFEST).is_file() {
+            Ok(Course::Dir(path))
+        } else if path.is_dir() {
+            Err(t!("course.dir_without_manifest", path.display(), MANIFEST))
+        } else {
+            Err(t!("course.not_found", path.display()))
+        }
+    }
+}
+
+// 课程包必须是 zip 压缩包, 并且其中有 info.toml
+fn check_archive(path: &Path) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| t!("course.open_failed", path.display(), e))?;
+    let mut zip = zip::ZipArchive::new(file)
+        .map_err(|_| t!("course.not_zip", path.display()))?;
+    match course_root(&mut zip) {
+        Some(_) => Ok(()),
+        None => Err(t!("course.archive_without_manifest", path.display(), MANIFEST)),
+    }
+}
+
+// 命令行参数还没有解析之前, 先找出 --course, 这样课程中的配置文件也能生效
+pub fn course_from_args() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--course" {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix("--course=").map(String::from)
+        }
+    })
+}
+
+// 当前课程的说明, 课程文件夹中没有说明文件时使用默认课程的说明
+pub fn welcome() -> String {
+    let names = match i18n::lang() {
+        Lang::En => [WELCOME_EN, WELCOME],
+        Lang::Zh => [WELCOME, WELCOME],
+    };
+    names
+        .iter()
+        .find_map(|name| fs::read_to_string(name).ok())
+        .unwrap_or_else(|| {
+            let name = names[0];
+            let (_, contents) = FILES.iter().find(|(file, _)| *file == name).unwrap();
+            String::from_utf8_lossy(contents).to_string()
+        })
+}
+
+// 把课程写入一个新的文件夹
+pub fn init(course: &Course, dir: &Path) -> Result<(), ()> {
+    let not_empty = fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some());
+    if not_empty {
+        warn!("{}", t!("course.dir_not_empty", dir.display()));
+        return Err(());
+    }
+    let files = files(course).map_err(|e| warn!("{}", t!("course.read_failed", e)))?;
+    if !files.contains_key(Path::new(MANIFEST)) {
+        warn!("{}", t!("course.no_manifest", MANIFEST));
+        return Err(());
+    }
+    let written = write_files(dir, &files)
+        // 学习记录和编译缓存不需要提交到学生自己的 git 仓库中
+        .and_then(|_| fs::write(dir.join(".gitignore"), ".clearning/\n"))
+        .and_then(|_| update::save_base(dir, &files));
+    if let Err(e) = written {
+        warn!("{}", t!("course.init_failed", e));
+        return Err(());
+    }
+
+    success!("{}", t!("course.created", dir.display()));
+    println!();
+    println!("{}", t!("course.next_steps"));
+    println!("    {}", style(format!("cd {}", dir.display())).bold());
+    println!("    {}", style("clearning watch").bold());
+    Ok(())
+}
+
+// 课程中的所有文件, key 是相对于课程根目录的路径
+pub fn files(course: &Course) -> io::Result<BTreeMap<PathBuf, Vec<u8>>> {
+    let mut files = BTreeMap::new();
+    match course {
+        Course::Embedded => {
+            for (name, contents) in FILES {
+                files.insert(PathBuf::from(name), contents.to_vec());
+            }
+            for (name, embedded) in [("exercises", &EXERCISES), ("harness", &HARNESS)].iter() {
+                embedded_files(embedded, Path::new(name), &mut files);
+            }
+        }
+        Course::Dir(dir) => dir_files(dir, Path::new(""), &mut files)?,
+        Course::Archive(archive) => archive_files(archive, &mut files)?,
+    }
+    Ok(files)
+}
+
+// 把课程的文件写入 dir 中
+pub fn write_files(dir: &Path, files: &BTreeMap<PathBuf, Vec<u8>>) -> io::Result<()> {
+    for (name, contents) in files {
+        let path = dir.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+    }
+    Ok(())
+}
+
+// include_dir 中文件的路径都是相对于嵌入的文件夹的, 子文件夹中的文件也是如此
+fn embedded_files(embedded: &Dir, root: &Path, files: &mut BTreeMap<PathBuf, Vec<u8>>) {
+    for entry in embedded.entries() {
+        match entry {
+            DirEntry::Dir(dir) => embedded_files(dir, root, files),
+            DirEntry::File(file) => {
+                files.insert(root.join(file.path()), file.contents().to_vec());
+            }
+        }
+    }
+}
+
+fn dir_files(dir: &Path, prefix: &Path, files: &mut BTreeMap<PathBuf, Vec<u8>>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if SKIPPED.iter().any(|skipped| name == *skipped) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            dir_files(&entry.path(), &prefix.join(&name), files)?;
+        } else {
+            files.insert(prefix.join(&name), fs::read(entry.path())?);
+        }
+    }
+    Ok(())
+}
+
+// zip 课程包中的文件, 课程可以在压缩包的最外层, 也可以在压缩包中唯一的文件夹里
+fn archive_files(archive: &Path, files: &mut BTreeMap<PathBuf, Vec<u8>>) -> io::Result<()> {
+    let invalid = |e: zip::result::ZipError| io::Error::new(io::ErrorKind::InvalidData, e);
+    let mut zip = zip::ZipArchive::new(File::open(archive)?).map_err(invalid)?;
+    let prefix = course_root(&mut zip).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, t!("course.archive_no_manifest", MANIFEST))
+    })?;
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i).map_err(invalid)?;
+        // 不使用路径在压缩包之外的文件, 例如 ../../etc/passwd
+        let name = match file.enclosed_name() {
+            Some(name) => name.to_path_buf(),
+            None => continue,
+        };
+        let name = match name.strip_prefix(&prefix) {
+            Ok(name) if !name.as_os_str().is_empty() => name.to_path_buf(),
+            _ => continue,
+        };
+        if !file.is_dir() {
+            let mut contents = Vec::new();
+            file.read_to_end(&mut contents)?;
+            files.insert(name, contents);
+        }
+    }
+    Ok(())
+}
+
+// 找出 info.toml 所在的文件夹, 它就是课程的根目录
+fn course_root(zip: &mut zip::ZipArchive<File>) -> Option<PathBuf> {
+    (0..zip.len())
+        .filter_map(|i| zip.by_index(i).ok()?.enclosed_name().map(Path::to_path_buf))
+        .filter(|name| name.file_name().is_some_and(|file| file == MANIFEST))
+        .map(|name| name.parent().map(Path::to_path_buf).unwrap_or_default())
+        .min_by_key(|root| root.components().count())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    // 测试根据 --course 参数判断课程的来源
+    #[test]
+    fn test_from_arg() {
+        assert_eq!(Course::from_arg(None), Ok(Course::Embedded));
+        assert_eq!(Course::from_arg(Some(".")), Ok(Course::Dir(PathBuf::from("."))));
+        assert!(Course::from_arg(Some("exercise_test")).is_err());
+        assert!(Course::from_arg(Some("没有这个课程")).is_err());
+    }
+
+    // 测试只有带 info.toml 的 zip 压缩包才是课程包
+    #[test]
+    fn test_from_arg_archive() {
+        let archive = std::env::temp_dir().join("clearning_test_course.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        let options = zip::write::FileOptions::default();
+        zip.start_file("course/info.toml", options).unwrap();
+        zip.write_all(b"exercises = []").unwrap();
+        zip.finish().unwrap();
+        let arg = archive.to_str().unwrap();
+        assert_eq!(Course::from_arg(Some(arg)), Ok(Course::Archive(archive.clone())));
+        let _ = fs::remove_file(&archive);
+
+        // xmind 文件也是 zip 压缩包, 但是里面没有 info.toml
+        let error = Course::from_arg(Some("C语言基础.xmind")).unwrap_err();
+        assert!(error.contains(MANIFEST));
+        assert!(Course::from_arg(Some("Cargo.toml")).is_err());
+    }
+}
@@ -0,0 +1,47 @@
+use crate::exercise::{Exercise, Mode};
+use crate::map;
+use std::path::Path;
+
+// 给题目作者用的检查: info.toml 中引用的文件是否存在, 以及课程导图中哪些主题还没有练习
+pub fn check(exercises: &[Exercise]) -> Result<(), ()> {
+    let mut errors = Vec::new();
+    for exercise in exercises {
+        let mut files: Vec<&Path> = exercise.source_files();
+        files.extend(exercise.notes.as_deref());
+        files.extend(exercise.test.as_deref());
+        for file in files.into_iter().filter(|file| !file.exists()) {
+            errors.push(t!("dev.missing_file", exercise.name, file.display()));
+        }
+        if exercise.mode == Mode::Test && exercise.test.is_none() {
+            errors.push(t!("dev.no_test", exercise.name));
+        }
+        if exercise.mode == Mode::Quiz && exercise.questions.is_empty() {
+            errors.push(t!("dev.no_questions", exercise.name));
+        }
+    }
+
+    match map::load(Path::new(map::COURSE_MAP)) {
+        Ok(root) => {
+            let uncovered = map::uncovered(&root, exercises);
+            if !uncovered.is_empty() {
+                warn!("{}", t!("dev.uncovered", uncovered.len()));
+                for topic in uncovered {
+                    println!("  {}", topic);
+                }
+                println!();
+            }
+        }
+        Err(e) => errors.push(e),
+    }
+
+    if errors.is_empty() {
+        success!("{}", t!("dev.ok", exercises.len()));
+        Ok(())
+    } else {
+        warn!("{}", t!("dev.errors", errors.len()));
+        for error in errors {
+            println!("  {}", error);
+        }
+        Err(())
+    }
+}
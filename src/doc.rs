@@ -0,0 +1,564 @@
+use crate::exercise::Exercise;
+use console::{measure_text_width, style, Style};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 讲义所在的文件夹
+const NOTES_DIR: &str = "exercises";
+
+// C 语言的关键词, 代码块中会高亮显示
+const C_KEYWORDS: [&str; 44] = [
+    "auto",
+    "break",
+    "case",
+    "char",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extern",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "register",
+    "restrict",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "switch",
+    "typedef",
+    "union",
+    "unsigned",
+    "void",
+    "volatile",
+    "while",
+    "_Alignas",
+    "_Alignof",
+    "_Atomic",
+    "_Bool",
+    "_Complex",
+    "_Generic",
+    "_Imaginary",
+    "_Noreturn",
+    "_Static_assert",
+    "_Thread_local",
+];
+
+// 所有可以查看的讲义: 练习中声明的讲义, 以及 exercises 文件夹中的 Markdown 文件
+// 返回 (主题, 文件路径), 练习的主题是练习的名字, Markdown 文件的主题是文件名
+pub fn topics(exercises: &[Exercise]) -> Vec<(String, PathBuf)> {
+    let mut topics: Vec<(String, PathBuf)> = exercises
+        .iter()
+        .filter_map(|e| {
+            e.notes
+                .as_ref()
+                .map(|notes| (e.name.clone(), notes.clone()))
+        })
+        .collect();
+    let mut files = Vec::new();
+    find_markdown(Path::new(NOTES_DIR), &mut files);
+    files.sort();
+    for file in files {
+        let stem = match file.file_stem().and_then(|stem| stem.to_str()) {
+            Some(stem) => stem.to_string(),
+            None => continue,
+        };
+        // 练习和讲义同名时, 只保留练习
+        if !topics.iter().any(|(name, _)| *name == stem) {
+            topics.push((stem, file));
+        }
+    }
+    topics
+}
+
+fn find_markdown(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if path.is_dir() {
+            find_markdown(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            files.push(path);
+        }
+    }
+}
+
+// 在终端中显示一份讲义
+pub fn show(path: &Path) -> Result<(), ()> {
+    match fs::read_to_string(path) {
+        Ok(markdown) => {
+            println!("{}", render(&markdown));
+            Ok(())
+        }
+        Err(e) => {
+            println!("{}", t!("doc.read_failed", path.display(), e));
+            Err(())
+        }
+    }
+}
+
+// 把 Markdown 渲染成带颜色的终端文本
+pub fn render(markdown: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut renderer = Renderer::default();
+    for event in Parser::new_ext(markdown, options) {
+        renderer.event(event);
+    }
+    renderer.finish()
+}
+
+// 正在渲染的表格, 第一行是表头
+#[derive(Default)]
+struct Table {
+    alignments: Vec<Alignment>,
+    rows: Vec<Vec<String>>,
+    cell: String,
+}
+
+#[derive(Default)]
+struct Renderer {
+    out: String,
+    // 当前标题的级别
+    heading: Option<HeadingLevel>,
+    // 嵌套的加粗、斜体和删除线层数
+    strong: usize,
+    emphasis: usize,
+    strikethrough: usize,
+    // 正在链接的文字中
+    link: bool,
+    // 引用块的嵌套层数
+    quote: usize,
+    // 每一层列表的下一个序号, 无序列表是 None
+    lists: Vec<Option<u64>>,
+    // 正在收集的代码块: (语言, 代码)
+    code: Option<(String, String)>,
+    table: Option<Table>,
+    // 链接的地址放在最后, 正文中只显示编号
+    links: Vec<String>,
+}
+
+impl Renderer {
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => match &mut self.code {
+                Some((_, code)) => code.push_str(&text),
+                None => self.text(&text),
+            },
+            Event::Code(code) => {
+                let code = style(code.to_string()).yellow().to_string();
+                self.push(&code);
+            }
+            Event::SoftBreak | Event::HardBreak => self.newline(),
+            Event::Rule => {
+                self.block();
+                let rule = style("─".repeat(40)).dim().to_string();
+                self.push(&rule);
+                self.out.push_str("\n\n");
+            }
+            Event::TaskListMarker(done) => self.push(if done { "[x] " } else { "[ ] " }),
+            Event::Html(_) | Event::FootnoteReference(_) => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.line_start(),
+            Tag::Heading(level, _, _) => {
+                self.block();
+                self.heading = Some(level);
+            }
+            Tag::BlockQuote => {
+                self.block();
+                self.quote += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.block();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(lang) => lang.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((lang, String::new()));
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.block();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                if !self.at_line_start() {
+                    self.out.push('\n');
+                }
+                self.line_start();
+                let depth = self.lists.len().saturating_sub(1);
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.out.push_str(&"  ".repeat(depth));
+                self.out.push_str(&bullet);
+            }
+            Tag::Table(alignments) => {
+                self.block();
+                self.table = Some(Table {
+                    alignments,
+                    ..Default::default()
+                });
+            }
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(table) = &mut self.table {
+                    table.rows.push(Vec::new());
+                }
+            }
+            Tag::TableCell => {}
+            Tag::Emphasis => self.emphasis += 1,
+            Tag::Strong => self.strong += 1,
+            Tag::Strikethrough => self.strikethrough += 1,
+            Tag::Link(_, url, _) | Tag::Image(_, url, _) => {
+                self.link = true;
+                self.links.push(url.to_string());
+            }
+            Tag::FootnoteDefinition(_) => {}
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => {
+                self.out.push('\n');
+                if self.lists.is_empty() {
+                    self.out.push('\n');
+                }
+            }
+            Tag::Heading(level, _, _) => {
+                self.heading = None;
+                self.out.push('\n');
+                if level == HeadingLevel::H1 {
+                    self.out.push_str(&style("═".repeat(40)).cyan().to_string());
+                    self.out.push('\n');
+                }
+                self.out.push('\n');
+            }
+            Tag::BlockQuote => self.quote -= 1,
+            Tag::CodeBlock(_) => {
+                if let Some((lang, code)) = self.code.take() {
+                    let code = match lang.as_str() {
+                        "" | "c" | "C" | "h" => highlight_c(&code),
+                        _ => code,
+                    };
+                    for line in code.trim_end_matches('\n').split('\n') {
+                        self.out.push_str("    ");
+                        self.out.push_str(line);
+                        self.out.push('\n');
+                    }
+                    self.out.push('\n');
+                }
+            }
+            Tag::List(_) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    if !self.at_line_start() {
+                        self.out.push('\n');
+                    }
+                    self.out.push('\n');
+                }
+            }
+            Tag::Item => {}
+            Tag::Table(_) => {
+                if let Some(table) = self.table.take() {
+                    self.out.push_str(&render_table(&table));
+                    self.out.push('\n');
+                }
+            }
+            Tag::TableCell => {
+                if let Some(table) = &mut self.table {
+                    let cell = std::mem::take(&mut table.cell);
+                    if let Some(row) = table.rows.last_mut() {
+                        row.push(cell);
+                    }
+                }
+            }
+            Tag::TableHead | Tag::TableRow => {}
+            Tag::Emphasis => self.emphasis -= 1,
+            Tag::Strong => self.strong -= 1,
+            Tag::Strikethrough => self.strikethrough -= 1,
+            Tag::Link(..) | Tag::Image(..) => {
+                self.link = false;
+                let number = style(format!("[{}]", self.links.len())).dim().to_string();
+                self.push(&number);
+            }
+            Tag::FootnoteDefinition(_) => {}
+        }
+    }
+
+    // 按照当前的样式输出一段文字
+    fn text(&mut self, text: &str) {
+        let mut text_style = Style::new();
+        match self.heading {
+            Some(HeadingLevel::H1) => text_style = text_style.cyan().bold().underlined(),
+            Some(HeadingLevel::H2) => text_style = text_style.cyan().bold(),
+            Some(_) => text_style = text_style.bold(),
+            None => {}
+        }
+        if self.strong > 0 {
+            text_style = text_style.bold();
+        }
+        if self.emphasis > 0 {
+            text_style = text_style.italic();
+        }
+        if self.strikethrough > 0 {
+            text_style = text_style.dim();
+        }
+        if self.link {
+            text_style = text_style.blue().underlined();
+        }
+        let text = text_style.apply_to(text).to_string();
+        self.push(&text);
+    }
+
+    // 表格中的文字放进当前的单元格, 其他的直接输出
+    fn push(&mut self, text: &str) {
+        match &mut self.table {
+            Some(table) => table.cell.push_str(text),
+            None => self.out.push_str(text),
+        }
+    }
+
+    fn newline(&mut self) {
+        if self.table.is_none() {
+            self.out.push('\n');
+            self.line_start();
+        }
+    }
+
+    // 在引用块中, 每一行的开头都有竖线
+    fn line_start(&mut self) {
+        if self.quote > 0 && self.at_line_start() {
+            let bar = style("│ ".repeat(self.quote)).dim().to_string();
+            self.out.push_str(&bar);
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    // 块级元素之前至少要换行
+    fn block(&mut self) {
+        if !self.at_line_start() {
+            self.out.push('\n');
+        }
+    }
+
+    // 在最后列出所有链接的地址
+    fn finish(mut self) -> String {
+        if !self.links.is_empty() {
+            for (index, url) in self.links.iter().enumerate() {
+                self.out.push_str(&format!(
+                    "{} {}\n",
+                    style(format!("[{}]", index + 1)).dim(),
+                    style(url).dim()
+                ));
+            }
+        }
+        self.out.trim_end().to_string()
+    }
+}
+
+// 用方框线画出表格, 宽度按照终端中显示的宽度计算, 中文占两格
+fn render_table(table: &Table) -> String {
+    let columns = table.rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            table
+                .rows
+                .iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| measure_text_width(cell))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut out = String::new();
+    for (index, row) in table.rows.iter().enumerate() {
+        out.push('│');
+        for (column, width) in widths.iter().enumerate() {
+            let cell = row.get(column).map(String::as_str).unwrap_or("");
+            let padding = width - measure_text_width(cell);
+            let (left, right) = match table.alignments.get(column) {
+                Some(Alignment::Right) => (padding, 0),
+                Some(Alignment::Center) => (padding / 2, padding - padding / 2),
+                _ => (0, padding),
+            };
+            let cell = if index == 0 {
+                style(cell).bold().to_string()
+            } else {
+                cell.to_string()
+            };
+            out.push_str(&format!(
+                " {}{}{} │",
+                " ".repeat(left),
+                cell,
+                " ".repeat(right)
+            ));
+        }
+        out.push('\n');
+        if index == 0 {
+            let lines: Vec<String> = widths.iter().map(|width| "─".repeat(width + 2)).collect();
+            out.push_str(&format!("├{}┤\n", lines.join("┼")));
+        }
+    }
+    out
+}
+
+// 给 C 代码加上颜色: 关键词、字符串、数字、注释和预处理指令
+pub fn highlight_c(code: &str) -> String {
+    let chars: Vec<char> = code.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    // 当前位置之前是否只有空白, 用来识别预处理指令
+    let mut line_start = true;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let token_style = if c == '#' && line_start {
+            i = end_of_line(&chars, i);
+            Some(Style::new().magenta())
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            i = end_of_line(&chars, i);
+            Some(Style::new().dim())
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i + 1 < chars.len() && !(chars[i] == '*' && chars[i + 1] == '/') {
+                i += 1;
+            }
+            i = (i + 2).min(chars.len());
+            Some(Style::new().dim())
+        } else if c == '"' || c == '\'' {
+            i += 1;
+            while i < chars.len() && chars[i] != c && chars[i] != '\n' {
+                if chars[i] == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+            Some(Style::new().green())
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            Some(Style::new().cyan())
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            if C_KEYWORDS.contains(&word.as_str()) {
+                Some(Style::new().blue().bold())
+            } else {
+                None
+            }
+        } else {
+            i += 1;
+            None
+        };
+
+        let token: String = chars[start..i].iter().collect();
+        match token_style {
+            Some(token_style) => out.push_str(&token_style.apply_to(&token).to_string()),
+            None => out.push_str(&token),
+        }
+        if token.contains('\n') {
+            line_start = true;
+        }
+        if !c.is_whitespace() {
+            line_start = false;
+        }
+    }
+    out
+}
+
+fn end_of_line(chars: &[char], from: usize) -> usize {
+    chars[from..]
+        .iter()
+        .position(|&c| c == '\n')
+        .map(|offset| from + offset)
+        .unwrap_or(chars.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试标题、表格、代码块和链接的渲染
+    #[test]
+    fn test_render() {
+        console::set_colors_enabled(false);
+        let markdown = "\
+# 整型
+
+| 类型 | 大小 |
+|------|-----:|
+| int | 4 |
+| 长整型 long | 8 |
+
+```c
+int main() { return 0; }
+```
+
+参见[整数类型](https://zh.cppreference.com/w/c/language/arithmetic_types)。
+";
+        let rendered = render(markdown);
+        assert_eq!(
+            rendered,
+            "\
+整型
+════════════════════════════════════════
+
+│ 类型        │ 大小 │
+├─────────────┼──────┤
+│ int         │    4 │
+│ 长整型 long │    8 │
+
+    int main() { return 0; }
+
+参见整数类型[1]。
+
+[1] https://zh.cppreference.com/w/c/language/arithmetic_types"
+        );
+    }
+
+    // 测试代码高亮不会改变代码本身
+    #[test]
+    fn test_highlight_c() {
+        let code = "#include <stdio.h>\n/* 注释 */ int a = 10; // x\nchar *s = \"a\\\"b\";\n";
+        let highlighted = highlight_c(code);
+        assert!(highlighted.contains(&style("int").blue().bold().to_string()));
+        assert_eq!(console::strip_ansi_codes(&highlighted), code);
+    }
+}
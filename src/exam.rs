@@ -0,0 +1,472 @@
+use crate::exercise::{Exercise, Input, Mode};
+use crate::harness;
+use crate::quiz;
+use crate::watch;
+use console::style;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// 每场考试的状态, key 是考试的名字
+const STATE: &str = ".clearning/exams.json";
+// 签名考试结果的密钥, 需要老师在编译 clearning 时用环境变量 CLEARNING_EXAM_KEY 设置
+// 开源代码中的默认密钥任何人都能用来伪造签名, 所以没有设置时不签名, 也不检查签名
+const KEY: Option<&str> = option_env!("CLEARNING_EXAM_KEY");
+
+// info.toml 中的一场考试
+#[derive(Deserialize)]
+pub struct Exam {
+    // 考试的名字, clearning exam <名字> 开始考试
+    pub name: String,
+    // 考试的时长
+    pub minutes: u64,
+    // 考试中的练习的 id
+    pub exercises: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    // 开始考试和必须交卷的时间, 都是 unix 时间戳 (秒)
+    started: u64,
+    deadline: u64,
+    #[serde(default)]
+    submitted: bool,
+}
+
+// 一个练习的得分: 通过了几项检查
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Score {
+    passed: usize,
+    total: usize,
+}
+
+impl Score {
+    // 满分 100
+    fn points(self) -> u32 {
+        if self.total == 0 {
+            return 0;
+        }
+        (self.passed * 100 / self.total) as u32
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExerciseResult {
+    id: String,
+    score: u32,
+    passed: usize,
+    checks: usize,
+    // 源文件在截止时间之后被修改过, 这个练习不计分
+    late: bool,
+    // 交卷时源文件的 sha256
+    files: BTreeMap<PathBuf, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExamResult {
+    exam: String,
+    started: u64,
+    deadline: u64,
+    // 实际交卷的时间
+    submitted: u64,
+    // 有练习在截止时间之后被修改过
+    late: bool,
+    score: u32,
+    total: u32,
+    exercises: Vec<ExerciseResult>,
+}
+
+// 写入结果文件的内容, signature 是其余内容的 HMAC-SHA256
+#[derive(Serialize, Deserialize)]
+struct SignedResult {
+    #[serde(flatten)]
+    result: ExamResult,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn load_states() -> BTreeMap<String, State> {
+    fs::read_to_string(STATE)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_states(states: &BTreeMap<String, State>) -> io::Result<()> {
+    fs::create_dir_all(Path::new(STATE).parent().unwrap())?;
+    fs::write(STATE, serde_json::to_string_pretty(states).unwrap())
+}
+
+// 正在进行的考试的名字
+fn running(states: &BTreeMap<String, State>) -> Option<&str> {
+    states
+        .iter()
+        .find(|(_, state)| !state.submitted && now() < state.deadline)
+        .map(|(name, _)| name.as_str())
+}
+
+// 考试期间不能查看提示
+pub fn in_progress() -> bool {
+    running(&load_states()).is_some()
+}
+
+// 结果文件的路径, 放在练习文件夹中方便交给老师
+fn result_path(exam: &str) -> PathBuf {
+    PathBuf::from(format!("exam-{}.json", exam))
+}
+
+// 检查每一条代码规则和结构化查询都算一项
+fn rule_count(exercise: &Exercise) -> usize {
+    let checks = &exercise.checks;
+    checks.required.len()
+        + checks.forbidden.len()
+        + checks.required_literals.len()
+        + checks.forbidden_literals.len()
+        + exercise.queries.len()
+}
+
+// 按照通过的检查给练习打分, 考试中不要求删除 I AM NOT DONE
+// 编译、运行各算一项, 隐藏测试的每一条断言、题目练习的每一道题也各算一项
+fn grade(exercise: &Exercise) -> Score {
+    let rules = rule_count(exercise);
+    let (passed, total) = match exercise.mode {
+        Mode::Quiz => {
+            let total = exercise.questions.len();
+            let wrong = quiz::grade(exercise).map_or(total, |wrong| wrong.len());
+            return Score {
+                passed: total - wrong.min(total),
+                total,
+            };
+        }
+        Mode::CompileFail => {
+            let passed = match exercise.compile() {
+                Ok(_) => false,
+                Err(output) => exercise.expects_error(&output.stderr),
+            };
+            return Score {
+                passed: passed as usize,
+                total: 1,
+            };
+        }
+        Mode::Compile | Mode::Run | Mode::Test => {
+            let compilation = match exercise.compile() {
+                Ok(compilation) => compilation,
+                // 没有通过编译时代码规则也不得分
+                Err(_) => {
+                    let total = if exercise.mode == Mode::Run { 2 } else { 1 };
+                    return Score {
+                        passed: 0,
+                        total: total + rules,
+                    };
+                }
+            };
+            match exercise.mode {
+                Mode::Compile => (1, 1),
+                Mode::Run => (1 + compilation.run(Input::Null).is_ok() as usize, 2),
+                _ => {
+                    let output = match compilation.run(Input::Null) {
+                        Ok(output) | Err(output) => output,
+                    };
+                    let report = harness::parse(&output.stdout);
+                    let passed = report.assertions.iter().filter(|a| a.passed).count();
+                    (passed, report.assertions.len().max(1))
+                }
+            }
+        }
+    };
+    let violations = exercise.check_source().len().min(rules);
+    Score {
+        passed: passed + rules - violations,
+        total: total + rules,
+    }
+}
+
+fn hash_file(path: &Path) -> String {
+    format!("{:x}", Sha256::digest(fs::read(path).unwrap_or_default()))
+}
+
+// 文件是否在 deadline 之后被修改过
+fn modified_after(path: &Path, deadline: u64) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .is_some_and(|modified| modified.as_secs() > deadline)
+}
+
+fn sign(result: &ExamResult, key: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).unwrap();
+    mac.update(serde_json::to_string(result).unwrap().as_bytes());
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+fn format_remaining(seconds: u64) -> String {
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn print_scores(exercises: &[&Exercise], scores: &[Score], deadline: u64) {
+    println!();
+    for (exercise, score) in exercises.iter().zip(scores) {
+        let points = format!("{:>3}", score.points());
+        let points = if score.passed == score.total {
+            style(points).green()
+        } else {
+            style(points).yellow()
+        };
+        println!(
+            "  {}",
+            t!("exam.score_line", points, exercise.name, score.passed, score.total)
+        );
+    }
+    let total: u32 = scores.iter().map(|score| score.points()).sum();
+    println!("  {}", t!("exam.total", total, scores.len() * 100));
+    println!(
+        "  {} {}",
+        crate::ui::emoji("⏱", "*"),
+        t!("exam.remaining", format_remaining(deadline.saturating_sub(now())))
+    );
+    println!();
+}
+
+// 考试中的命令行, 收到的命令交给考试的主循环处理
+fn spawn_exam_shell() -> Receiver<String> {
+    let (tx, rx) = channel();
+    println!("{}", t!("exam.help"));
+    thread::spawn(move || loop {
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input).is_err() || input.is_empty() {
+            return;
+        }
+        let input = input.trim();
+        if input == "hint" {
+            println!("{}", t!("exam.hint_locked"));
+        } else if tx.send(input.to_string()).is_err() {
+            return;
+        }
+    });
+    rx
+}
+
+// exam: 在限定的时间内完成一组练习, 时间到了或者交卷之后写入结果文件
+pub fn exam(exams: &[Exam], exercises: &[Exercise], name: &str) -> Result<(), ()> {
+    let exam = match exams.iter().find(|exam| exam.name == name) {
+        Some(exam) => exam,
+        None => {
+            warn!("{}", t!("exam.not_found", name));
+            exams.iter().for_each(|exam| println!("    {}", exam.name));
+            return Err(());
+        }
+    };
+    let mut selected = Vec::new();
+    for id in &exam.exercises {
+        match exercises.iter().find(|e| e.id() == id) {
+            Some(exercise) => selected.push(exercise),
+            None => {
+                warn!("{}", t!("exam.unknown_exercise", id));
+                return Err(());
+            }
+        }
+    }
+
+    // 中途退出后再次运行时继续之前的考试, 不会重新计时
+    let mut states = load_states();
+    if let Some(running) = running(&states).filter(|running| *running != exam.name) {
+        warn!("{}", t!("exam.running", running));
+        return Err(());
+    }
+    let state = match states.remove(&exam.name) {
+        Some(state) if state.submitted => {
+            warn!("{}", t!("exam.submitted", result_path(&exam.name).display()));
+            return Err(());
+        }
+        Some(state) => state,
+        None => State {
+            started: now(),
+            deadline: now() + exam.minutes * 60,
+            submitted: false,
+        },
+    };
+    states.insert(exam.name.clone(), state);
+    save_states(&states).map_err(|e| warn!("{}", t!("exam.start_failed", e)))?;
+    let state = &states[&exam.name];
+    if now() >= state.deadline {
+        warn!("{}", t!("exam.time_over"));
+    }
+
+    println!(
+        "{}",
+        t!("exam.intro", style(&exam.name).bold(), selected.len(), exam.minutes)
+    );
+    let mut scores: Vec<Score> = selected.iter().map(|exercise| grade(exercise)).collect();
+    print_scores(&selected, &scores, state.deadline);
+
+    if now() < state.deadline {
+        let (_watcher, rx) =
+            watch::start_watcher(Path::new("./exercises")).map_err(|e| warn!("{:?}", e))?;
+        let commands = spawn_exam_shell();
+        let mut reminded = state.deadline.saturating_sub(now()) / 60;
+        'exam: while now() < state.deadline {
+            let changed = match watch::next_changes(&rx, Some(Duration::from_secs(1))) {
+                Some(changed) => changed,
+                None => break,
+            };
+            let mut regraded = false;
+            for (exercise, score) in selected.iter().zip(scores.iter_mut()) {
+                if changed.iter().any(|path| exercise.owns(path)) {
+                    *score = grade(exercise);
+                    regraded = true;
+                }
+            }
+            if regraded {
+                print_scores(&selected, &scores, state.deadline);
+            }
+            for command in commands.try_iter() {
+                match command.as_str() {
+                    "submit" => break 'exam,
+                    "score" => print_scores(&selected, &scores, state.deadline),
+                    "time" => println!(
+                        "{}",
+                        t!("exam.remaining", format_remaining(state.deadline.saturating_sub(now())))
+                    ),
+                    "" => {}
+                    other => println!("{}", t!("watch.unknown", other)),
+                }
+            }
+            // 最后五分钟每分钟提醒一次, 之前每十分钟提醒一次
+            let minutes = state.deadline.saturating_sub(now()) / 60;
+            if minutes < reminded && (minutes < 5 || minutes % 10 == 0) {
+                println!("{} {}", crate::ui::emoji("⏱", "*"), t!("exam.reminder", minutes + 1));
+            }
+            reminded = reminded.min(minutes);
+        }
+    }
+
+    let (started, deadline) = (state.started, state.deadline);
+    submit(exam, &selected, started, deadline)
+}
+
+// 交卷: 重新打分, 写入签名的结果文件
+// 截止时间之后修改过的练习不计分
+fn submit(exam: &Exam, exercises: &[&Exercise], started: u64, deadline: u64) -> Result<(), ()> {
+    println!("{}", t!("exam.grading"));
+    let mut scores: Vec<Score> = exercises.iter().map(|exercise| grade(exercise)).collect();
+    let mut results = Vec::new();
+    for (exercise, score) in exercises.iter().zip(scores.iter_mut()) {
+        let sources = exercise.source_files();
+        let late = sources.iter().any(|path| modified_after(path, deadline));
+        if late {
+            warn!("{}", t!("exam.late", exercise));
+            score.passed = 0;
+        }
+        results.push(ExerciseResult {
+            id: exercise.id().to_string(),
+            score: score.points(),
+            passed: score.passed,
+            checks: score.total,
+            late,
+            files: sources
+                .into_iter()
+                .map(|path| (path.to_path_buf(), hash_file(path)))
+                .collect(),
+        });
+    }
+    let result = ExamResult {
+        exam: exam.name.clone(),
+        started,
+        deadline,
+        submitted: now(),
+        late: results.iter().any(|result| result.late),
+        score: results.iter().map(|result| result.score).sum(),
+        total: (results.len() * 100) as u32,
+        exercises: results,
+    };
+    let signature = KEY.map(|key| sign(&result, key));
+    let path = result_path(&exam.name);
+    let signed = SignedResult { result, signature };
+    let mut states = load_states();
+    if let Some(state) = states.get_mut(&exam.name) {
+        state.submitted = true;
+    }
+    let written = fs::write(&path, serde_json::to_string_pretty(&signed).unwrap())
+        .and_then(|_| save_states(&states));
+    if let Err(e) = written {
+        warn!("{}", t!("exam.write_failed", e));
+        return Err(());
+    }
+
+    print_scores(exercises, &scores, deadline);
+    if signed.signature.is_none() {
+        warn!("{}", t!("exam.unsigned"));
+    }
+    success!("{}", t!("exam.written", path.display()));
+    Ok(())
+}
+
+// exam --verify: 检查结果文件的签名, 以及交卷之后源文件是否被修改过
+pub fn verify(path: &Path) -> Result<(), ()> {
+    let text = fs::read_to_string(path).map_err(|e| warn!("{}", t!("exam.read_failed", e)))?;
+    let signed: SignedResult =
+        serde_json::from_str(&text).map_err(|e| warn!("{}", t!("exam.bad_result", e)))?;
+    let key = KEY.ok_or_else(|| warn!("{}", t!("exam.no_key")))?;
+    let signature = signed
+        .signature
+        .as_ref()
+        .ok_or_else(|| warn!("{}", t!("exam.no_signature", path.display())))?;
+    if sign(&signed.result, key) != *signature {
+        warn!("{}", t!("exam.bad_signature", path.display()));
+        return Err(());
+    }
+    let result = &signed.result;
+    success!("{}", t!("exam.verified", result.exam, result.score, result.total));
+    if result.late {
+        warn!("{}", t!("exam.late_result"));
+    }
+    for exercise in &result.exercises {
+        for (file, hash) in &exercise.files {
+            if hash_file(file) != *hash {
+                println!("  {}", t!("exam.changed_after_submit", file.display()));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试修改分数之后签名不再正确
+    #[test]
+    fn test_sign() {
+        let mut result = ExamResult {
+            exam: "测验".to_string(),
+            started: 0,
+            deadline: 60,
+            submitted: 60,
+            late: false,
+            score: 50,
+            total: 100,
+            exercises: Vec::new(),
+        };
+        let signature = sign(&result, "密钥");
+        assert_eq!(signature, sign(&result, "密钥"));
+        assert_ne!(signature, sign(&result, "另一个密钥"));
+        result.score = 100;
+        assert_ne!(signature, sign(&result, "密钥"));
+        assert_eq!(Score { passed: 1, total: 3 }.points(), 33);
+        assert_eq!(Score { passed: 0, total: 0 }.points(), 0);
+    }
+}
@@ -1,12 +1,26 @@
+use crate::cache;
+use crate::check::{self, Checks, Violation};
+use crate::config;
+use crate::exam::Exam;
+use crate::i18n::{self, Lang};
+use crate::leak;
+use crate::query::{self, Query};
+use crate::quiz::Question;
+use crate::sanitizer;
+use crate::ui;
 use regex::Regex;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::fmt::{self, Display, Formatter};
-use std::fs::{remove_file, File};
-use std::io::Read;
-use std::process::{self, Command};
-use std::path::PathBuf;
+use std::fs::{self, remove_file, File};
+use std::io::{self, Read, Write};
+use std::process::{self, Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::path::{Component, Path, PathBuf};
 
-const I_AM_NOT_DONE_REGEX: &str = r"(?m)^\s*///?\s*I\s+AM\s+NOT\s+DONE";
+// C 代码中用 // 注释, Makefile 中用 # 注释
+const I_AM_NOT_DONE_REGEX: &str = r"(?m)^\s*(///?|#)\s*I\s+AM\s+NOT\s+DONE";
 const CONTEXT: usize = 2;
 
 #[inline]
@@ -18,19 +32,154 @@ fn temp_file() -> String {
     format!("./temp_{}_{}", process::id(), thread_id)
 }
 
+// 编译隐藏测试时, 学生代码的目标文件
+#[inline]
+fn temp_object() -> String {
+    format!("{}.o", temp_file())
+}
+
+// 构建 make 练习的沙盒文件夹, 练习文件夹会被复制到这里再运行 make
+#[inline]
+fn temp_build_dir() -> String {
+    format!("{}_build", temp_file())
+}
+
 #[derive(Deserialize)]
 pub struct ExerciseList {
+    // 课程的版本, 每次发布新的课程时加一, update 根据它判断是否需要更新
+    #[serde(default)]
+    pub version: Option<u32>,
     pub exercises: Vec<Exercise>,
+    // 限时完成一组练习的考试
+    #[serde(default)]
+    pub exams: Vec<Exam>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 pub struct Exercise {
     // 练习的名字
     pub name: String,
-    // 练习源代码的文件路径
+    // 练习的标识, 练习改名或者移动之后也保持不变, 没有写时使用练习的名字
+    #[serde(default)]
+    pub id: Option<String>,
+    // 练习源代码的文件路径, I AM NOT DONE 写在这个文件中
     pub path: PathBuf,
-    // 练习的文字提示
-    pub hint: String,
+    // 练习所在的章节, 默认是 exercises 下的第一层文件夹
+    #[serde(default)]
+    pub chapter: Option<String>,
+    // 练习所在的小节, 默认是 exercises 下的第二层文件夹
+    #[serde(default)]
+    pub section: Option<String>,
+    // 需要先完成的练习的 id, 为了兼容旧的课程, 也可以写练习的名字
+    #[serde(default)]
+    pub requires: Vec<String>,
+    // 练习的标签, 和课程导图中的主题同名时, 练习属于这个主题
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // 练习的难度, 从 1 到 5
+    #[serde(default)]
+    pub difficulty: Option<u8>,
+    // 预计完成这个练习需要的分钟数
+    #[serde(default)]
+    pub estimated_minutes: Option<u32>,
+    // 练习的模式: 怎样判断这个练习通过了, 默认需要编译并成功运行
+    #[serde(default)]
+    pub mode: Mode,
+    // compile_fail 练习预期的编译错误, 以 re: 开头的是正则表达式, 否则只要错误信息中包含它就可以
+    #[serde(default)]
+    pub expected_error: Option<String>,
+    // 练习的构建方式, 默认直接调用 gcc
+    #[serde(default)]
+    pub build: Build,
+    // make 练习构建出的可执行文件的名字 (相对于练习文件夹)
+    #[serde(default)]
+    pub artifact: Option<String>,
+    // 除了 path 之外, 还需要一起编译的源文件
+    #[serde(default)]
+    pub sources: Vec<PathBuf>,
+    // 头文件所在的文件夹, 编译时会通过 -I 传给 gcc
+    #[serde(default)]
+    pub include_dirs: Vec<PathBuf>,
+    // 练习的文字提示, 可以写成 hint.zh 和 hint.en 给出不同语言的提示
+    pub hint: Hint,
+    // 练习对应的 Markdown 讲义, 可以用 doc 命令查看
+    #[serde(default)]
+    pub notes: Option<PathBuf>,
+    // test 模式下隐藏测试的源代码路径, 它会和练习文件链接在一起, 调用学生写的函数
+    #[serde(default)]
+    pub test: Option<PathBuf>,
+    // 是否开启 AddressSanitizer 和 UndefinedBehaviorSanitizer 检查内存错误
+    #[serde(default)]
+    pub sanitize: bool,
+    // 是否检查内存泄漏和非法读写 (有 valgrind 时使用 valgrind, 否则使用 LeakSanitizer)
+    #[serde(default)]
+    pub leak_check: bool,
+    // 对源代码的静态检查: 必须使用和不允许使用的写法
+    #[serde(default)]
+    pub checks: Checks,
+    // 在语法树上进行的结构化检查, 例如 "定义了有 3 个成员的结构体"
+    #[serde(default)]
+    pub queries: Vec<Query>,
+    // quiz 练习的题目, 学生的答案写在 path 指定的答案文件中
+    #[serde(default)]
+    pub questions: Vec<Question>,
+}
+
+// 练习的模式
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    // 只需要通过编译
+    Compile,
+    // 需要通过编译并成功运行
+    #[default]
+    Run,
+    // 和隐藏测试一起编译, 并通过隐藏测试中所有的断言
+    Test,
+    // 学生需要写出会产生特定编译错误的代码, 学会阅读编译器的报错
+    CompileFail,
+    // 不需要编译, 批改答案文件中的选择题和填空题
+    Quiz,
+}
+
+// 练习的构建方式
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Build {
+    // 用 gcc 直接编译练习的源文件
+    #[default]
+    Gcc,
+    // path 指向练习文件夹中的 Makefile, 在沙盒中运行 make 构建练习
+    Make,
+}
+
+// 练习的提示, 可以是一段文字, 也可以是按照语言代码给出的多段文字
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(untagged)]
+pub enum Hint {
+    Plain(String),
+    Localized(BTreeMap<String, String>),
+}
+
+impl Default for Hint {
+    fn default() -> Self {
+        Hint::Plain(String::new())
+    }
+}
+
+impl Hint {
+    // 当前语言的提示, 没有翻译时使用中文提示
+    pub fn text(&self) -> &str {
+        match self {
+            Hint::Plain(text) => text,
+            Hint::Localized(texts) => texts
+                .get(i18n::lang().code())
+                .or_else(|| texts.get(Lang::Zh.code()))
+                .or_else(|| texts.values().next())
+                .map(String::as_str)
+                .unwrap_or(""),
+        }
+    }
 }
 
 // 一个表示练习状态的枚举
@@ -56,16 +205,29 @@ pub struct ContextLine {
 // 表示一个 exercise 的编译结果
 pub struct CompiledExercise<'a> {
     exercise: &'a Exercise,
+    // 通过编译时编译器给出的警告
+    pub warnings: String,
     _handle: FileHandle,
 }
 
 impl<'a> CompiledExercise<'a> {
     // 运行已经编译的 exercise
-    pub fn run(&self) -> Result<ExerciseOutput, ExerciseOutput> {
-        self.exercise.run()
+    pub fn run(&self, input: Input) -> Result<ExerciseOutput, ExerciseOutput> {
+        self.exercise.run(input)
     }
 }
 
+// 练习程序的标准输入
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Input<'a> {
+    // 没有输入, 程序调用 scanf 时会立即读到 EOF
+    Null,
+    // 连接到终端, 学生可以直接输入, 程序的标准输出也直接显示在终端上
+    Terminal,
+    // 从文件中读取输入
+    File(&'a Path),
+}
+
 // 表示一个已经运行的二进制文件
 #[derive(Debug)]
 pub struct ExerciseOutput {
@@ -87,17 +249,62 @@ impl Drop for FileHandle {
 }
 
 impl Exercise {
+    pub fn id(&self) -> &str {
+        self.id.as_deref().unwrap_or(&self.name)
+    }
+
     // 将一个练习文件编译成 exe
-    pub fn compile(&self) -> Result<CompiledExercise, ExerciseOutput> {
+    pub fn compile(&self) -> Result<CompiledExercise<'_>, ExerciseOutput> {
+        // 源文件和编译参数都没有变过时, 直接使用之前编译出的可执行文件
+        let key = self.cache_key();
+        if self.mode != Mode::Quiz {
+            if let Some(warnings) = cache::load(&key, Path::new(&temp_file())) {
+                ui::verbose(&t!("exercise.cached", self));
+                return Ok(CompiledExercise {
+                    exercise: self,
+                    warnings,
+                    _handle: FileHandle,
+                });
+            }
+        }
         // println!("while compile: {}", &temp_file());
-        let cmd = Command::new("gcc")
-            .args(&[self.path.to_str().unwrap(), "-o", &temp_file()])
-            .output()
-            .expect("Failed to run 'complie' command,");
-        
+        let cmd = match (self.build, self.mode, &self.test) {
+            (_, Mode::Quiz, _) => {
+                return Err(ExerciseOutput {
+                    stdout: String::new(),
+                    stderr: t!("exercise.quiz_not_compiled", self.name),
+                })
+            }
+            (Build::Make, _, _) => self.build_with_make(),
+            (Build::Gcc, Mode::Test, Some(test)) => self.compile_with_test(test),
+            (Build::Gcc, Mode::Test, None) => {
+                return Err(ExerciseOutput {
+                    stdout: String::new(),
+                    stderr: t!("exercise.no_test", self.name),
+                })
+            }
+            (Build::Gcc, _, _) => output_of(
+                Command::new(config::get().compiler())
+                    .args([self.path.to_str().unwrap(), "-o", &temp_file()])
+                    .args(&self.sources)
+                    .args(self.include_args())
+                    .args(self.flags()),
+            ),
+        };
+
             if cmd.status.success() {
+                if let Err(message) = self.take_artifact() {
+                    clean();
+                    return Err(ExerciseOutput {
+                        stdout: String::from_utf8_lossy(&cmd.stdout).to_string(),
+                        stderr: message,
+                    });
+                }
+                let warnings = String::from_utf8_lossy(&cmd.stderr).to_string();
+                cache::store(&key, Path::new(&temp_file()), &warnings);
                 Ok(CompiledExercise {
-                    exercise: &self,
+                    exercise: self,
+                    warnings,
                     _handle: FileHandle,
                 })
             } else {
@@ -109,18 +316,217 @@ impl Exercise {
             }
     }
 
+    // 把练习文件夹复制到沙盒中, 然后在沙盒中运行 make
+    // 这样 make 产生的中间文件不会弄乱学生的练习文件夹
+    fn build_with_make(&self) -> Output {
+        let sandbox = PathBuf::from(temp_build_dir());
+        let _ignored = fs::remove_dir_all(&sandbox);
+        copy_dir(self.dir(), &sandbox).expect("Unable to copy the exercise directory!");
+        output_of(
+            Command::new("make")
+                .arg("--no-print-directory")
+                .current_dir(&sandbox),
+        )
+    }
+
+    // make 练习构建成功后, 把构建出的可执行文件放到运行的位置
+    fn take_artifact(&self) -> Result<(), String> {
+        if self.build != Build::Make {
+            return Ok(());
+        }
+        let artifact = self.artifact.as_deref().unwrap_or("main");
+        fs::copy(Path::new(&temp_build_dir()).join(artifact), temp_file())
+            .map(|_| ())
+            .map_err(|_| t!("exercise.no_artifact", artifact))
+    }
+
+    // 练习所在的文件夹
+    fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or_else(|| Path::new("."))
+    }
+
+    // 练习文件在 exercises 中所在的各层文件夹, 例如 ["类型系统", "基础类型", "整型"]
+    fn course_dirs(&self) -> Vec<&str> {
+        let mut dirs: Vec<&str> = self
+            .dir()
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => name.to_str(),
+                _ => None,
+            })
+            .collect();
+        if dirs.first() == Some(&"exercises") {
+            dirs.remove(0);
+        }
+        dirs
+    }
+
+    // 练习所在的章节, 直接放在 exercises 中的练习不属于任何章节, 归入 "未分类"
+    pub fn chapter_name(&self) -> &str {
+        match &self.chapter {
+            Some(chapter) => chapter,
+            None => self
+                .course_dirs()
+                .first()
+                .copied()
+                .unwrap_or(t!("exercise.uncategorized")),
+        }
+    }
+
+    // 用星号表示的难度和预计时间, 例如 "★★☆☆☆ 10 分钟"
+    pub fn metadata(&self) -> String {
+        let mut metadata = Vec::new();
+        if let Some(difficulty) = self.difficulty {
+            let difficulty = usize::from(difficulty.min(5));
+            metadata.push(format!("{}{}", "★".repeat(difficulty), "☆".repeat(5 - difficulty)));
+        }
+        if let Some(minutes) = self.estimated_minutes {
+            metadata.push(t!("exercise.minutes", minutes));
+        }
+        metadata.join(" ")
+    }
+
+    // 练习所在的小节, 章节下面直接放着的练习没有小节
+    pub fn section_name(&self) -> Option<&str> {
+        match &self.section {
+            Some(section) => Some(section),
+            None => self.course_dirs().get(1).copied(),
+        }
+    }
+
+    // 将练习文件和隐藏测试一起编译
+    // 练习文件的 main 函数会被改名为 clearning_student_main, 由测试代码的 main 调用学生写的函数
+    fn compile_with_test(&self, test: &Path) -> Output {
+        let student = output_of(
+            Command::new(config::get().compiler())
+                .arg("-c")
+                .arg(&self.path)
+                .args(["-Dmain=clearning_student_main", "-o", &temp_object()])
+                .args(self.include_args())
+                .args(self.flags()),
+        );
+        if !student.status.success() {
+            return student;
+        }
+
+        output_of(
+            Command::new(config::get().compiler())
+                .arg(temp_object())
+                .args(&self.sources)
+                .arg(test)
+                .args(["-o", &temp_file()])
+                .args(self.include_args())
+                .args(self.flags()),
+        )
+    }
+
+    // 除了源文件和输出文件之外, 还要传给 gcc 的参数
+    fn flags(&self) -> Vec<&'static str> {
+        let mut flags = Vec::new();
+        if self.sanitize {
+            flags.extend(&sanitizer::FLAGS);
+        } else if self.leak_check && !self.use_valgrind() {
+            flags.extend(&leak::LSAN_FLAGS);
+        } else if self.leak_check {
+            // valgrind 需要调试信息才能给出行号
+            flags.push("-g");
+        }
+        if !self.sources.is_empty() && !flags.contains(&"-g") {
+            // 有了调试信息, 链接器报错时会给出是哪个源文件的哪一行, 而不是临时的 .o 文件
+            flags.push("-g");
+        }
+        flags.extend(config::get().flags().iter().map(String::as_str));
+        flags
+    }
+
+    // 编译缓存的 key: 编译方式、编译参数以及所有可能影响编译结果的文件
+    pub fn cache_key(&self) -> String {
+        let options = format!(
+            "{:?} {:?} {:?} {:?} {:?}",
+            self.build,
+            self.mode,
+            self.artifact,
+            self.flags(),
+            self.include_args()
+        );
+        let mut inputs = self.source_files();
+        inputs.extend(self.test.as_deref());
+        inputs.extend(self.include_dirs.iter().map(PathBuf::as_path));
+        if self.build == Build::Make {
+            inputs.push(self.dir());
+        }
+        cache::key(&options, &inputs)
+    }
+
+    // 头文件文件夹对应的 -I 参数
+    fn include_args(&self) -> Vec<String> {
+        self.include_dirs
+            .iter()
+            .map(|dir| format!("-I{}", dir.display()))
+            .collect()
+    }
+
+    // 练习包含的所有源文件: path 和 sources
+    pub fn source_files(&self) -> Vec<&Path> {
+        std::iter::once(self.path.as_path())
+            .chain(self.sources.iter().map(PathBuf::as_path))
+            .collect()
+    }
+
+    // 开启了 sanitizer 的程序不能在 valgrind 下运行
+    fn use_valgrind(&self) -> bool {
+        self.leak_check && !self.sanitize && leak::valgrind_exists()
+    }
+
     // 运行编译出的 exe 并返回运行结果
-    fn run(&self) -> Result<ExerciseOutput, ExerciseOutput> {
+    // 连接到终端时不限制运行时间, 返回的 stdout 为空
+    fn run(&self, input: Input) -> Result<ExerciseOutput, ExerciseOutput> {
         // 运行编译出的 exe 
         // println!("while run: {}", &temp_file());
-        let cmd = Command::new(&temp_file())
-            .output()
+        let mut command = if self.use_valgrind() {
+            let mut valgrind = Command::new("valgrind");
+            valgrind.args(leak::VALGRIND_ARGS).arg(temp_file());
+            valgrind
+        } else {
+            Command::new(temp_file())
+        };
+        if self.sanitize && !self.leak_check {
+            // 只检查越界等错误时, 不让 LeakSanitizer 因为内存泄漏而让程序失败
+            command.env("ASAN_OPTIONS", "detect_leaks=0");
+        }
+        let timeout = match input {
+            Input::Null => {
+                command.stdin(Stdio::null());
+                config::get().timeout()
+            }
+            Input::Terminal => {
+                command.stdin(Stdio::inherit()).stdout(Stdio::inherit());
+                None
+            }
+            Input::File(path) => {
+                let file = File::open(path).map_err(|e| ExerciseOutput {
+                    stdout: String::new(),
+                    stderr: t!("exercise.input_failed", path.display(), e),
+                })?;
+                command.stdin(file);
+                config::get().timeout()
+            }
+        };
+        ui::command(&command);
+        let (cmd, timed_out) = output_with_timeout(&mut command, timeout, input == Input::Terminal)
             .expect("Failed to run 'run' command");
-        
-        let output = ExerciseOutput {
+
+        let mut output = ExerciseOutput {
             stdout: String::from_utf8_lossy(&cmd.stdout).to_string(),
             stderr: String::from_utf8_lossy(&cmd.stderr).to_string(),
         };
+        if timed_out {
+            output.stderr.push_str(&t!(
+                "exercise.timed_out",
+                config::get().timeout().unwrap_or_default().as_secs()
+            ));
+            return Err(output);
+        }
 
         // 返回编译结果
         if cmd.status.success() {
@@ -130,6 +536,64 @@ impl Exercise {
         }
     }
 
+    // 判断被修改的文件是否属于这个练习: 它的源文件, 头文件文件夹中的文件, 或者 make 练习文件夹中的文件
+    pub fn owns(&self, path: &Path) -> bool {
+        let changed = match path.canonicalize() {
+            Ok(changed) => changed,
+            Err(_) => return false,
+        };
+        let is_source = self
+            .source_files()
+            .iter()
+            .filter_map(|source| source.canonicalize().ok())
+            .any(|source| source == changed);
+        let in_include_dir = self
+            .include_dirs
+            .iter()
+            .filter_map(|dir| dir.canonicalize().ok())
+            .any(|dir| changed.starts_with(dir));
+        // make 练习中, 练习文件夹里的任何文件都可能影响构建结果
+        let in_make_dir = self.build == Build::Make
+            && self
+                .dir()
+                .canonicalize()
+                .is_ok_and(|dir| changed.starts_with(dir));
+        is_source || in_include_dir || in_make_dir
+    }
+
+    // 判断编译错误是不是 compile_fail 练习预期的那一个
+    pub fn expects_error(&self, stderr: &str) -> bool {
+        let expected = match &self.expected_error {
+            Some(expected) => expected,
+            None => return true,
+        };
+        match expected.strip_prefix(check::REGEX_PREFIX) {
+            Some(pattern) => Regex::new(pattern).is_ok_and(|re| re.is_match(stderr)),
+            None => stderr.contains(expected.as_str()),
+        }
+    }
+
+    // 按照 info.toml 中声明的规则和结构化查询检查源代码
+    pub fn check_source(&self) -> Vec<Violation> {
+        let source = fs::read_to_string(&self.path).expect("Unable to read the exercise file!");
+        let mut violations = self.checks.check(&source);
+        if !self.queries.is_empty() {
+            violations.extend(query::run(&self.queries, &self.path, &self.include_dirs));
+        }
+        violations
+    }
+
+    // 删掉练习文件中 I AM NOT DONE 所在的行, 其他行 (包括换行符) 保持不变
+    pub fn remove_marker(&self) -> io::Result<()> {
+        let source = fs::read_to_string(&self.path)?;
+        let re = Regex::new(I_AM_NOT_DONE_REGEX).unwrap();
+        let kept: String = source
+            .split_inclusive('\n')
+            .filter(|line| !re.is_match(line))
+            .collect();
+        fs::write(&self.path, kept)
+    }
+
     // 获取一个练习所处的状态
     pub fn state(&self) -> State {
         let mut source_file = 
@@ -174,6 +638,90 @@ impl Exercise {
     }
 }
 
+// 运行编译命令, -v 时先输出这条命令
+// 所有的编译 (包括 make 和隐藏测试) 都使用 C locale
+// compile_fail 练习要和英文的错误信息比较, 不能使用翻译过的错误信息, 也不能使用 UTF-8 的弯引号
+fn output_of(command: &mut Command) -> Output {
+    ui::command(command);
+    command
+        .env("LC_ALL", "C")
+        .output()
+        .expect("Failed to run 'complie' command,")
+}
+
+// 运行练习程序并收集输出, 超过 timeout 时杀掉进程, 返回的 bool 表示是否超时
+// --nocapture 时程序的输出会同时显示在终端上
+// interactive 时标准输出已经连接到终端, 只收集标准错误
+fn output_with_timeout(
+    command: &mut Command,
+    timeout: Option<Duration>,
+    interactive: bool,
+) -> io::Result<(Output, bool)> {
+    let echo = ui::nocapture() || interactive;
+    if timeout.is_none() && !echo {
+        return command.output().map(|output| (output, false));
+    }
+    if !interactive {
+        command.stdout(Stdio::piped());
+    }
+    let mut child = command.stderr(Stdio::piped()).spawn()?;
+    // 在另外的线程中读取输出, 以免管道写满之后程序卡住
+    let stdout = read_in_thread(
+        child.stdout.take(),
+        echo.then(|| Box::new(io::stdout()) as Box<dyn Write + Send>),
+    );
+    let stderr = read_in_thread(
+        child.stderr.take(),
+        echo.then(|| Box::new(io::stderr()) as Box<dyn Write + Send>),
+    );
+
+    let started = Instant::now();
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+            timed_out = true;
+            let _ = child.kill();
+            break child.wait()?;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    let output = Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    };
+    Ok((output, timed_out))
+}
+
+// 读取管道中的全部输出, echo 不为 None 时一边读一边写到 echo 中
+fn read_in_thread<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    mut echo: Option<Box<dyn Write + Send>>,
+) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let mut pipe = match pipe {
+            Some(pipe) => pipe,
+            None => return buffer,
+        };
+        let mut chunk = [0; 4096];
+        while let Ok(n) = pipe.read(&mut chunk) {
+            if n == 0 {
+                break;
+            }
+            if let Some(echo) = &mut echo {
+                let _ = echo.write_all(&chunk[..n]);
+                let _ = echo.flush();
+            }
+            buffer.extend_from_slice(&chunk[..n]);
+        }
+        buffer
+    })
+}
+
 impl Display for Exercise {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{}", self.path.to_str().unwrap())
@@ -182,7 +730,24 @@ impl Display for Exercise {
 
 #[inline]
 fn clean() {
-    let _ignored = remove_file(&temp_file());
+    let _ignored = remove_file(temp_file());
+    let _ignored = remove_file(temp_object());
+    let _ignored = fs::remove_dir_all(temp_build_dir());
+}
+
+// 递归地复制文件夹
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -197,11 +762,11 @@ mod test {
     // 测试 clean 函数是否正常运行
     #[test]
     fn test_clean() {
-        File::create(&temp_file()).unwrap();
+        File::create(temp_file()).unwrap();
         let exercise = Exercise {
             name: String::from("example"),
             path: PathBuf::from("exercise_test/HelloWorld.c"),
-            hint: String::from(""),
+            ..Default::default()
         };
         let compiled = exercise.compile().unwrap();
         drop(compiled);
@@ -214,9 +779,9 @@ mod test {
         let exercise = Exercise {
             name: String::from("example"),
             path: PathBuf::from("exercise_test/HelloWorldTWO.c"),
-            hint: String::from(""),
+            ..Default::default()
         };
-        let result = exercise.compile().unwrap().run().unwrap();
+        let result = exercise.compile().unwrap().run(Input::Null).unwrap();
         assert!(result.stdout.contains("Hello World!"));
     }
 
@@ -226,7 +791,7 @@ mod test {
         let exercise = Exercise {
             name: String::from("example"),
             path: PathBuf::from("exercise_test/HelloWorldThree.c"),
-            hint: String::from(""),
+            ..Default::default()
         };
 
         let state = exercise.state();
@@ -260,4 +825,172 @@ mod test {
 
         assert_eq!(state, State::Pending(expected));
     }
-}
\ No newline at end of file
+
+    // 测试 owns 函数只认当前练习的源文件
+    #[test]
+    fn test_owns() {
+        let exercise = Exercise {
+            name: String::from("example"),
+            path: PathBuf::from("exercise_test/HelloWorld.c"),
+            ..Default::default()
+        };
+
+        assert!(exercise.owns(Path::new("./exercise_test/../exercise_test/HelloWorld.c")));
+        assert!(!exercise.owns(Path::new("exercise_test/HelloWorldTWO.c")));
+        assert!(!exercise.owns(Path::new("exercise_test/NotExists.c")));
+    }
+
+    // 测试隐藏测试能调用练习文件中的函数
+    #[test]
+    fn test_compile_with_test() {
+        let exercise = Exercise {
+            name: String::from("example"),
+            path: PathBuf::from("exercise_test/Function.c"),
+            mode: Mode::Test,
+            test: Some(PathBuf::from("exercise_test/FunctionTest.c")),
+            ..Default::default()
+        };
+        let result = exercise.compile().unwrap().run(Input::Null).unwrap();
+        let report = crate::harness::parse(&result.stdout);
+        assert_eq!(report.assertions.len(), 2);
+        assert!(report.assertions[0].passed);
+        assert!(!report.assertions[1].passed);
+    }
+
+    // 测试从文件读取标准输入, 没有输入时 scanf 读到 EOF
+    #[test]
+    fn test_run_with_input() {
+        let exercise = Exercise {
+            name: String::from("example"),
+            path: PathBuf::from("exercise_test/Sum.c"),
+            ..Default::default()
+        };
+        let compiled = exercise.compile().unwrap();
+        let result = compiled.run(Input::File(Path::new("exercise_test/Sum.txt")));
+        assert_eq!(result.unwrap().stdout, "7\n");
+        assert_eq!(compiled.run(Input::Null).unwrap_err().stdout, "no input\n");
+    }
+
+    // 测试多个源文件和头文件文件夹
+    #[test]
+    fn test_multiple_files() {
+        let exercise = Exercise {
+            name: String::from("example"),
+            path: PathBuf::from("exercise_test/multi/main.c"),
+            sources: vec![PathBuf::from("exercise_test/multi/greet.c")],
+            include_dirs: vec![PathBuf::from("exercise_test/multi/include")],
+            ..Default::default()
+        };
+        let result = exercise.compile().unwrap().run(Input::Null).unwrap();
+        assert!(result.stdout.contains("Hello World!"));
+        assert!(exercise.owns(Path::new("exercise_test/multi/greet.c")));
+        assert!(exercise.owns(Path::new("exercise_test/multi/include/greet.h")));
+        assert!(!exercise.owns(Path::new("exercise_test/HelloWorld.c")));
+    }
+
+    // 测试 make 练习在沙盒中构建, 不会在练习文件夹中留下中间文件
+    #[test]
+    fn test_build_with_make() {
+        let exercise = Exercise {
+            name: String::from("example"),
+            path: PathBuf::from("exercise_test/make/Makefile"),
+            build: Build::Make,
+            artifact: Some(String::from("hello")),
+            ..Default::default()
+        };
+        let result = exercise.compile().unwrap().run(Input::Null).unwrap();
+        assert!(result.stdout.contains("Hello World!"));
+        assert!(!Path::new("exercise_test/make/hello").exists());
+        assert!(!Path::new("exercise_test/make/main.o").exists());
+        assert!(exercise.owns(Path::new("exercise_test/make/main.c")));
+    }
+
+    // 测试从目录结构得到章节和小节, 以及 info.toml 中的覆盖
+    #[test]
+    fn test_chapter() {
+        let exercise = Exercise {
+            path: PathBuf::from("exercises/类型系统/基础类型/整型/int01.c"),
+            ..Default::default()
+        };
+        assert_eq!(exercise.chapter_name(), "类型系统");
+        assert_eq!(exercise.section_name(), Some("基础类型"));
+
+        let exercise = Exercise {
+            path: PathBuf::from("exercises/HelloWorld.c"),
+            ..Default::default()
+        };
+        assert_eq!(exercise.chapter_name(), t!("exercise.uncategorized"));
+        assert_eq!(exercise.section_name(), None);
+
+        let exercise = Exercise {
+            path: PathBuf::from("exercises/HelloWorld.c"),
+            chapter: Some(String::from("C程序基础结构")),
+            ..Default::default()
+        };
+        assert_eq!(exercise.chapter_name(), "C程序基础结构");
+    }
+
+    // 测试 compile_fail 练习的预期编译错误
+    #[test]
+    fn test_expects_error() {
+        let exercise = Exercise {
+            name: String::from("example"),
+            path: PathBuf::from("exercise_test/Undeclared.c"),
+            mode: Mode::CompileFail,
+            expected_error: Some(String::from("re:'\\w+' undeclared")),
+            ..Default::default()
+        };
+        let output = exercise.compile().err().unwrap();
+        assert!(exercise.expects_error(&output.stderr));
+        assert!(!exercise.expects_error("error: expected ';' before '}' token"));
+    }
+
+    // 测试在 UTF-8 的 locale 下, 编译错误仍然是 C locale 的写法
+    // locale 只设置在这条命令上, 不修改测试进程的环境变量
+    #[test]
+    fn test_expects_error_with_utf8_locale() {
+        let exercise = Exercise {
+            name: String::from("example"),
+            path: PathBuf::from("exercise_test/Undeclared.c"),
+            mode: Mode::CompileFail,
+            expected_error: Some(String::from("re:'\\w+' undeclared")),
+            ..Default::default()
+        };
+        let output = output_of(
+            Command::new(config::get().compiler())
+                .args([exercise.path.to_str().unwrap(), "-o", &temp_file()])
+                .env("LC_ALL", "C.UTF-8")
+                .env("LANG", "zh_CN.UTF-8"),
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(exercise.expects_error(&stderr));
+        assert!(!stderr.contains('\u{2018}'));
+    }
+
+    // 测试 info.toml 中两种写法的提示, 以及没有翻译时退回中文
+    #[test]
+    fn test_hint() {
+        let list: ExerciseList = toml::from_str(
+            r#"
+            [[exercises]]
+            name = "a"
+            path = "a.c"
+            hint = "提示"
+
+            [[exercises]]
+            name = "b"
+            path = "b.c"
+            hint.en = "hint"
+            hint.zh = "提示"
+
+            [[exercises]]
+            name = "c"
+            path = "c.c"
+            hint.en = "hint"
+            "#,
+        )
+        .unwrap();
+        let hints: Vec<&str> = list.exercises.iter().map(|e| e.hint.text()).collect();
+        assert_eq!(hints, vec!["提示", "提示", "hint"]);
+    }
+}
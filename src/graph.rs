@@ -0,0 +1,152 @@
+use crate::exercise::{Exercise, State};
+use std::collections::{HashMap, HashSet};
+
+// 前置练习用练习的 id 表示, 为了兼容旧的课程, 也可以写练习的名字
+fn index(exercises: &[Exercise]) -> HashMap<&str, usize> {
+    let mut index: HashMap<&str, usize> = exercises
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (e.id(), i))
+        .collect();
+    for (i, exercise) in exercises.iter().enumerate() {
+        index.entry(exercise.name.as_str()).or_insert(i);
+    }
+    index
+}
+
+// 检查 info.toml 中的前置练习: 不能引用不存在的练习, 也不能出现循环依赖
+pub fn validate(exercises: &[Exercise]) -> Result<(), String> {
+    let index = index(exercises);
+    for exercise in exercises {
+        if let Some(name) = exercise
+            .requires
+            .iter()
+            .find(|name| !index.contains_key(name.as_str()))
+        {
+            return Err(t!("graph.unknown_requirement", exercise.name, name));
+        }
+    }
+
+    // 深度优先搜索, 0 是没有访问过, 1 是在当前的搜索路径上, 2 是已经检查完了
+    fn visit(
+        i: usize,
+        exercises: &[Exercise],
+        index: &HashMap<&str, usize>,
+        color: &mut [u8],
+        path: &mut Vec<usize>,
+    ) -> Result<(), String> {
+        color[i] = 1;
+        path.push(i);
+        for name in &exercises[i].requires {
+            let j = index[name.as_str()];
+            match color[j] {
+                0 => visit(j, exercises, index, color, path)?,
+                1 => {
+                    let start = path.iter().position(|&k| k == j).unwrap();
+                    let mut cycle: Vec<&str> = path[start..]
+                        .iter()
+                        .map(|&k| exercises[k].name.as_str())
+                        .collect();
+                    cycle.push(&exercises[j].name);
+                    return Err(t!("graph.cycle", cycle.join(" -> ")));
+                }
+                _ => {}
+            }
+        }
+        path.pop();
+        color[i] = 2;
+        Ok(())
+    }
+
+    let mut color = vec![0; exercises.len()];
+    for i in 0..exercises.len() {
+        if color[i] == 0 {
+            visit(i, exercises, &index, &mut color, &mut Vec::new())?;
+        }
+    }
+    Ok(())
+}
+
+// 已经完成的练习的 id
+pub fn done(exercises: &[Exercise]) -> HashSet<&str> {
+    exercises
+        .iter()
+        .filter(|e| e.state() == State::Done)
+        .map(|e| e.id())
+        .collect()
+}
+
+// 还没有完成的前置练习的名字, 为空说明这个练习已经解锁了
+pub fn missing<'a>(
+    exercise: &Exercise,
+    exercises: &'a [Exercise],
+    done: &HashSet<&str>,
+) -> Vec<&'a str> {
+    let index = index(exercises);
+    exercise
+        .requires
+        .iter()
+        .filter_map(|required| index.get(required.as_str()))
+        .map(|&i| &exercises[i])
+        .filter(|required| !done.contains(required.id()))
+        .map(|required| required.name.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn exercise(name: &str, requires: &[&str]) -> Exercise {
+        Exercise {
+            name: name.to_string(),
+            requires: requires.iter().map(|r| r.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    // 测试不存在的前置练习和循环依赖
+    #[test]
+    fn test_validate() {
+        let exercises = vec![
+            exercise("int01", &[]),
+            exercise("数组01", &["int01"]),
+            exercise("指针01", &["数组01", "int01"]),
+        ];
+        assert!(validate(&exercises).is_ok());
+
+        let exercises = vec![exercise("int01", &["指针01"])];
+        assert!(validate(&exercises).unwrap_err().contains("指针01"));
+
+        let exercises = vec![
+            exercise("int01", &["指针01"]),
+            exercise("数组01", &["int01"]),
+            exercise("指针01", &["数组01"]),
+        ];
+        assert_eq!(
+            validate(&exercises).unwrap_err(),
+            "前置练习出现了循环: int01 -> 指针01 -> 数组01 -> int01"
+        );
+    }
+
+    // 测试根据完成的练习计算是否解锁, 前置练习可以写 id 也可以写名字
+    #[test]
+    fn test_missing() {
+        let exercises = vec![
+            Exercise {
+                id: Some("int-01".to_string()),
+                ..exercise("int01", &[])
+            },
+            Exercise {
+                id: Some("array-01".to_string()),
+                ..exercise("数组01", &[])
+            },
+            exercise("指针01", &["array-01", "int-01"]),
+            exercise("指针02", &["数组01", "int01"]),
+        ];
+        assert!(validate(&exercises).is_ok());
+        let done: HashSet<&str> = ["int-01"].iter().copied().collect();
+        assert_eq!(missing(&exercises[2], &exercises, &done), vec!["数组01"]);
+        assert_eq!(missing(&exercises[3], &exercises, &done), vec!["数组01"]);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::ui::emoji;
+use console::style;
+
+// 隐藏测试 (harness/clearning_test.h) 中每一条 CHECK 输出的前缀
+const PREFIX: &str = "[clearning] ";
+
+// 隐藏测试中一条断言的结果
+#[derive(PartialEq, Debug)]
+pub struct Assertion {
+    // 断言是否通过
+    pub passed: bool,
+    // 断言的文字描述
+    pub description: String,
+}
+
+// 表示隐藏测试的运行结果
+#[derive(PartialEq, Debug)]
+pub struct TestReport {
+    // 按照输出顺序排列的断言结果
+    pub assertions: Vec<Assertion>,
+    // 去掉断言之后, 程序自己的输出
+    pub output: String,
+}
+
+impl TestReport {
+    // 只有所有断言都通过, 并且至少有一条断言时才算通过
+    pub fn passed(&self) -> bool {
+        !self.assertions.is_empty() && self.assertions.iter().all(|a| a.passed)
+    }
+
+    // 逐条输出断言的结果
+    pub fn print_assertions(&self) {
+        for assertion in &self.assertions {
+            if assertion.passed {
+                println!("  {} {}", style(emoji("✅", "✓")).green(), assertion.description);
+            } else {
+                println!(
+                    "  {} {}",
+                    style(emoji("❌", "✗")).red(),
+                    style(&assertion.description).red()
+                );
+            }
+        }
+        if self.assertions.is_empty() {
+            println!("  {}", style(t!("harness.no_assertions")).red());
+        }
+    }
+}
+
+// 从测试程序的 stdout 中解析出每一条断言的结果
+pub fn parse(stdout: &str) -> TestReport {
+    let mut assertions = Vec::new();
+    let mut output = String::new();
+    for line in stdout.lines() {
+        let assertion = line.strip_prefix(PREFIX).and_then(|rest| {
+            if let Some(description) = rest.strip_prefix("PASS ") {
+                Some((true, description))
+            } else {
+                rest.strip_prefix("FAIL ").map(|description| (false, description))
+            }
+        });
+        match assertion {
+            Some((passed, description)) => assertions.push(Assertion {
+                passed,
+                description: description.to_string(),
+            }),
+            None => {
+                output.push_str(line);
+                output.push('\n');
+            }
+        }
+    }
+    TestReport { assertions, output }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试断言行会被解析出来, 其他输出原样保留
+    #[test]
+    fn test_parse() {
+        let report = parse("hello\n[clearning] PASS add 正确\n[clearning] FAIL max 错误\nbye\n");
+        assert_eq!(
+            report.assertions,
+            vec![
+                Assertion {
+                    passed: true,
+                    description: "add 正确".to_string(),
+                },
+                Assertion {
+                    passed: false,
+                    description: "max 错误".to_string(),
+                },
+            ]
+        );
+        assert_eq!(report.output, "hello\nbye\n");
+        assert!(!report.passed());
+    }
+}
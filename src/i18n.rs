@@ -0,0 +1,412 @@
+use std::env;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+// 界面使用的语言
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Lang {
+    Zh,
+    En,
+}
+
+impl Lang {
+    // info.toml 中本地化提示使用的语言代码, 例如 hint.en
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::Zh => "zh",
+            Lang::En => "en",
+        }
+    }
+
+    // 识别 "en"、"zh_CN.UTF-8" 这样的语言名, 不认识的语言返回 None
+    fn from_name(name: &str) -> Option<Lang> {
+        let name = name.to_ascii_lowercase();
+        if name.starts_with("zh") {
+            Some(Lang::Zh)
+        } else if name.starts_with("en") {
+            Some(Lang::En)
+        } else {
+            None
+        }
+    }
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+// 决定界面语言: 优先使用 --lang, 其次是 LC_ALL、LC_MESSAGES 和 LANG 环境变量, 都没有时使用中文
+pub fn init(flag: Option<&str>) {
+    let lang = flag
+        .and_then(Lang::from_name)
+        .or_else(|| {
+            ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .filter_map(|var| env::var(var).ok())
+                .find(|value| !value.is_empty())
+                .and_then(|value| Lang::from_name(&value))
+        })
+        .unwrap_or(Lang::Zh);
+    let _ = LANG.set(lang);
+}
+
+pub fn lang() -> Lang {
+    *LANG.get_or_init(|| Lang::Zh)
+}
+
+// 命令行参数还没有解析之前, 先找出 --lang, 这样帮助信息也能使用对应的语言
+pub fn lang_from_args() -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--lang" {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix("--lang=").map(String::from)
+        }
+    })
+}
+
+// 按照当前的语言查找消息, 没有翻译时退回中文
+pub fn message(key: &str) -> &'static str {
+    message_in(lang(), key)
+}
+
+fn message_in(lang: Lang, key: &str) -> &'static str {
+    match CATALOG.iter().find(|(k, _, _)| *k == key) {
+        Some((_, _, en)) if lang == Lang::En && !en.is_empty() => en,
+        Some((_, zh, _)) => zh,
+        None => panic!("消息目录中没有 {}", key),
+    }
+}
+
+// 把消息中的 {} 依次替换成参数
+pub fn format(template: &str, args: &[&dyn Display]) -> String {
+    let mut parts = template.split("{}");
+    let mut out = parts.next().unwrap_or("").to_string();
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+// t!("key") 返回当前语言的消息, t!("key", a, b) 返回替换了参数的消息
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::message($key)
+    };
+    ($key:literal, $($arg:expr),+) => {
+        $crate::i18n::format(
+            $crate::i18n::message($key),
+            &[$(&$arg as &dyn std::fmt::Display),+],
+        )
+    };
+}
+
+// 消息目录: (key, 中文, English), 英文为空时使用中文
+const CATALOG: &[(&str, &str, &str)] = &[
+    // 命令行
+    ("app.about", "clearning 是一个 c语言小练习的合集.希望通过 clearning ,你能够对 c语言的语法有初步的了解,并且养成良好的代码风格.", "clearning is a collection of small C exercises. It will get you used to reading and writing C and to a good coding style."),
+    ("arg.lang", "界面语言, 例如 zh 或者 en", "Language of the interface, e.g. zh or en"),
+    ("arg.course", "使用另一个课程: 课程文件夹, 或者用 init 解压一个 zip 课程包", "Use another course: a course folder, or a zip course pack to unpack with init"),
+    ("arg.nocapture", "练习程序运行时直接显示它的输出, 适合运行时间长或者需要交互的练习", "Stream the output of the exercises live while they run"),
+    ("arg.verbose", "同时输出编译命令、编译和运行的用时", "Also show the compiler commands and how long compiling and running took"),
+    ("arg.quiet", "不输出编译器的警告", "Hide compiler warnings"),
+    ("arg.sanitize", "用 AddressSanitizer 和 UndefinedBehaviorSanitizer 编译所有练习, 检查越界访问等内存错误", "Build every exercise with AddressSanitizer and UndefinedBehaviorSanitizer to catch memory errors"),
+    ("arg.chapter", "只做这一章的练习, 例如 --chapter 类型系统", "Only work through one chapter, e.g. --chapter 类型系统"),
+    ("arg.tag", "只要带有这个标签的练习, 例如 --tag 指针", "Only use exercises with this tag, e.g. --tag 指针"),
+    ("value.chapter", "章节", "CHAPTER"),
+    ("value.course", "课程", "COURSE"),
+    ("value.tag", "标签", "TAG"),
+    ("arg.interactive", "把终端连接到练习程序, 可以直接输入 scanf 读取的内容", "Attach the terminal to the program so you can type the input scanf reads"),
+    ("arg.input", "从文件中读取练习程序的输入", "Feed the program's input from a file"),
+    ("value.file", "文件", "FILE"),
+    ("arg.depth", "只显示这么多层主题", "Only show this many levels of topics"),
+    ("arg.difficulty", "只列出这个难度 (1 到 5) 的练习", "Only list exercises of this difficulty (1 to 5)"),
+    ("arg.sort", "不分章节, 按照难度、预计时间或者名字排序", "Sort by difficulty, estimated time or name instead of grouping by chapter"),
+    ("cmd.verify", "按照推荐顺序检验所有的练习", "Verify all exercises in the recommended order"),
+    ("cmd.watch", "每当当前练习文件被修改, 就会自动检验", "Re-verify the current exercise whenever it is saved"),
+    ("cmd.run", "运行单个练习文件", "Run a single exercise"),
+    ("cmd.quiz", "在终端中回答一组题目", "Answer a quiz in the terminal"),
+    ("cmd.hint", "给出当前练习的提示", "Show the hint of an exercise"),
+    ("cmd.doc", "在终端中查看讲义, 不指定主题时列出所有的讲义", "Read the notes in the terminal, or list all notes when no topic is given"),
+    ("cmd.map", "按照课程导图列出所有的主题和完成情况", "Show the course map with the progress of every topic"),
+    ("cmd.dev", "给题目作者使用的命令", "Commands for exercise authors"),
+    ("cmd.dev.check", "检查 info.toml, 并列出课程导图中还没有练习的主题", "Check info.toml and list the topics of the course map without exercises"),
+    ("cmd.config", "查看和修改 clearning 的配置, 不指定子命令时列出所有的配置", "Show or change the settings, or list all settings when no subcommand is given"),
+    ("cmd.config.get", "输出一个配置项的值", "Print the value of a setting"),
+    ("cmd.config.set", "修改课程配置文件 clearning.toml 中的一个配置项", "Change a setting in the course settings file clearning.toml"),
+    ("cmd.config.list", "列出所有的配置项", "List all settings"),
+    ("arg.global", "修改用户配置文件, 而不是课程配置文件", "Change the user settings file instead of the course one"),
+    ("cmd.init", "在一个新的文件夹中创建一份全新的练习", "Create a fresh copy of the course in a new folder"),
+    ("arg.dir", "练习所在的文件夹", "Folder for the exercises"),
+    ("cmd.update", "把新版本的课程合并到当前的练习中, 保留你修改过的代码", "Merge a new version of the course into your exercises, keeping your edits"),
+    ("cmd.exam", "开始一场限时考试, 考试中不能查看提示", "Start a timed exam, hints are locked during the exam"),
+    ("arg.exam", "考试的名字, 写在 info.toml 的 [[exams]] 中", "Name of the exam, as listed under [[exams]] in info.toml"),
+    ("arg.exam_verify", "检查考试结果文件的签名和源文件", "Check the signature and the sources of an exam result file"),
+    ("cmd.cache", "管理编译缓存", "Manage the build cache"),
+    ("cmd.cache.clean", "删除所有的编译缓存", "Delete the whole build cache"),
+    ("cmd.list", "按照章节列出当前所有的练习和进度", "List all exercises and the progress by chapter"),
+    // main.rs
+    ("main.open_in_folder", "请在 clearning 文件夹中打开 {}", "Please run {} inside the clearning folder"),
+    ("main.try_cd", "试着输入命令 `cd clearning/`, 或者用 `clearning init` 创建一份练习!", "Try `cd clearning/`, or create the exercises with `clearning init`!"),
+    ("main.start_with_init", "输入 `clearning init` 创建一份练习, 然后打开创建的 clearning 文件夹开始练习.", "Run `clearning init` to create the exercises, then start from the new clearning folder."),
+    ("main.archive_needs_init", "课程包 {} 需要先解压到一个文件夹中: clearning --course {} init <文件夹>", "The course pack {} has to be unpacked first: clearning --course {} init <folder>"),
+    ("main.no_gcc", "找不到 {} 编译器.", "Cannot find the {} compiler."),
+    ("main.gcc_version", "试着输入命令 `{} --version` 看看有什么问题.", "Try `{} --version` to see what is wrong."),
+    ("main.install_gcc", "百度一下: 怎么安装 gcc", "Search the web for how to install gcc"),
+    ("main.bad_info", "info.toml 有错误: {}", "info.toml is invalid: {}"),
+    ("main.duplicate_id", "有多个练习的 id 都是 {}", "more than one exercise has the id {}"),
+    ("main.bad_difficulty", "练习 {} 的难度必须在 1 到 5 之间", "the difficulty of {} must be between 1 and 5"),
+    ("main.no_exercise", "没找到这个练习! 看看是不是输错名字了!", "No such exercise! Check the name."),
+    ("main.no_quiz", "没找到这组题目! 看看是不是输错名字了!", "No such quiz! Check the name."),
+    ("main.locked", "这个练习还没有解锁, 建议先完成: {}", "This exercise is still locked, finish these first: {}"),
+    ("main.no_doc", "没找到这份讲义! 输入 `clearning doc` 看看有哪些讲义", "No such notes! Run `clearning doc` to list them"),
+    ("main.bad_difficulty_arg", "--difficulty 需要一个 1 到 5 的数字", "--difficulty takes a number from 1 to 5"),
+    ("main.bad_depth", "--depth 需要一个数字", "--depth takes a number"),
+    ("main.watch_error", "Error: 无法监视你的程序. 错误信息: {}.", "Error: cannot watch your files: {}."),
+    ("main.watch_error_reason", "很可能是因为你磁盘内存满了 或者 练习文件夹无法访问.", "Maybe the disk is full or the exercises folder cannot be accessed."),
+    ("main.chapter_done", "恭喜你完成了 {} 这一章的练习!", "Congratulations, you finished the chapter {}!"),
+    ("main.all_done", "恭喜你完成了所有的练习!", "Congratulations, you finished all the exercises!"),
+    ("main.motto", "千里之行,始于足下", "A journey of a thousand miles begins with a single step"),
+    ("main.farewell", "希望通过 clearining 的练习，你对 C语言有了初步的了解.", "We hope clearning gave you a first feel for C."),
+    ("main.learn_more", "如果想要更加深入地了解 C语言 或者 C++, 你可以复制下方链接, 到浏览器进行浏览:", "To learn more about C or C++, open this link in your browser:"),
+    ("main.reference", "https://zh.cppreference.com/", "https://en.cppreference.com/"),
+    // verify.rs 和 run.rs
+    ("verify.path", "练习文件的路径: {}\n", "Exercise file: {}\n"),
+    ("verify.cached", "{} 没有修改, 之前已经通过了", "{} is unchanged and passed before"),
+    ("verify.compiling", "正在编译 {}...", "Compiling {}..."),
+    ("verify.running", "正在运行 {}...", "Running {}..."),
+    ("verify.compile_time", "编译用时", "Compile time"),
+    ("verify.run_time", "运行用时", "Run time"),
+    ("verify.compiled", "{} 成功通过编译!", "Successfully compiled {}!"),
+    ("verify.compile_failed", "{} 编译失败! 再试一次. 这一次的输出如下:", "Compiling {} failed! Please try again. Here's the output:"),
+    ("verify.unexpected_success", "{} 通过了编译, 但是这个练习需要你写出无法通过编译的代码", "{} compiled, but this exercise asks for code that does not compile"),
+    ("verify.expected_error", "{} 产生了预期的编译错误!", "{} produced the expected compiler error!"),
+    ("verify.wrong_error", "{} 编译失败了, 但不是这个练习预期的错误. 这一次的输出如下:", "{} failed to compile, but not with the expected error. Here's the output:"),
+    ("verify.expected_error_is", "预期的编译错误: {}\n", "Expected compiler error: {}\n"),
+    ("verify.quiz_passed", "{} 的题目全部答对了!", "All answers in {} are correct!"),
+    ("verify.memory_error", "{} 存在内存错误或未定义行为:\n", "{} has memory errors or undefined behavior:\n"),
+    ("verify.leak", "{} 存在内存泄漏或非法读写:\n", "{} leaks memory or accesses invalid memory:\n"),
+    ("verify.run_failed", "{} 有如下错误", "Ran {} with errors"),
+    ("verify.test_failed", "{} 没有通过隐藏测试, 看看哪一条断言失败了", "{} failed the hidden tests, see which checks failed"),
+    ("verify.test_passed", "{} 通过了所有隐藏测试!", "{} passed all hidden tests!"),
+    ("verify.ran", "{} 成功运行!", "Successfully ran {}!"),
+    ("verify.check_failed", "{} 通过了编译, 但是没有通过代码检查:\n", "{} compiles, but does not pass the code checks:\n"),
+    ("verify.celebrate_error", "🎉 🎉  找到了预期的编译错误! 🎉 🎉", "🎉 🎉  Found the expected compiler error! 🎉 🎉"),
+    ("verify.celebrate_quiz", "🎉 🎉  全部答对了! 🎉 🎉", "🎉 🎉  All answers are correct! 🎉 🎉"),
+    ("verify.celebrate", "🎉 🎉  通过编译了! 🎉 🎉", "🎉 🎉  The code compiles! 🎉 🎉"),
+    ("verify.output", "输出:", "Output:"),
+    ("verify.advanced", "已经自动删除了 I AM NOT DONE, 继续下一个练习", "Removed I AM NOT DONE, moving on to the next exercise"),
+    ("verify.keep_working", "你可以继续改一下这一次练习的代码,", "You can keep working on this exercise,"),
+    ("verify.remove_marker", "或者去掉 {} 这个注释，然后做下一个练习:", "or remove the {} comment to move on to the next exercise:"),
+    ("run.compiled", "{} 成功通过编译", "Successfully compiled {}"),
+    ("run.compile_failed", "{} 编译失败, 错误信息如下:\n", "Compilation of {} failed, the compiler says:\n"),
+    ("run.unexpected_success", "{} 通过了编译, 但是这个练习需要产生编译错误", "{} compiled, but this exercise needs a compiler error"),
+    ("run.wrong_error", "{} 的编译错误不是这个练习预期的错误", "The compiler error of {} is not the expected one"),
+    ("run.expected_error", "{} 产生了预期的编译错误", "{} produced the expected compiler error"),
+    ("run.test_passed", "{} 通过了所有隐藏测试", "{} passed all hidden tests"),
+    ("run.test_failed", "{} 没有通过隐藏测试", "{} failed the hidden tests"),
+    ("run.ran", "成功运行 {}", "Successfully ran {}"),
+    // exam.rs
+    ("exam.hint_locked", "考试中不能查看提示", "Hints are locked during the exam"),
+    ("exam.score_line", "{} 分  {} ({}/{} 项检查)", "{} pts  {} ({}/{} checks)"),
+    ("exam.total", "总分 {} / {}", "Total {} / {}"),
+    ("exam.remaining", "剩余时间 {}", "Time left {}"),
+    ("exam.help", "输入 'score' 查看得分 输入 'time' 查看剩余时间 输入 'submit' 交卷", "Type 'score' for the scores, 'time' for the time left or 'submit' to hand in"),
+    ("exam.not_found", "没有这场考试: {}", "No such exam: {}"),
+    ("exam.unknown_exercise", "info.toml 有错误: 考试中的练习 {} 不存在", "info.toml is invalid: the exam exercise {} does not exist"),
+    ("exam.running", "考试 {} 还没有结束", "The exam {} has not ended yet"),
+    ("exam.submitted", "这场考试已经交卷了, 结果在 {} 中", "This exam was already handed in, the result is in {}"),
+    ("exam.start_failed", "无法开始考试: {}", "Cannot start the exam: {}"),
+    ("exam.time_over", "考试时间已经结束了, 截止时间之后修改过的练习不计分", "The exam time is over, exercises changed after the deadline score nothing"),
+    ("exam.intro", "{} 共 {} 个练习, 限时 {} 分钟, 考试中不能查看提示", "{}: {} exercises in {} minutes, hints are locked during the exam"),
+    ("exam.reminder", "还剩 {} 分钟", "{} minutes left"),
+    ("exam.grading", "考试结束, 正在批改...", "The exam is over, grading..."),
+    ("exam.late", "{} 在截止时间之后被修改过, 不计分", "{} was changed after the deadline and scores nothing"),
+    ("exam.write_failed", "无法写入考试结果: {}", "Cannot write the exam result: {}"),
+    ("exam.unsigned", "编译 clearning 时没有设置 CLEARNING_EXAM_KEY, 考试结果没有签名", "clearning was built without CLEARNING_EXAM_KEY, the exam result is not signed"),
+    ("exam.written", "考试结果已经写入 {}, 请把它交给老师", "The exam result was saved to {}, hand it to your teacher"),
+    ("exam.read_failed", "无法读取考试结果: {}", "Cannot read the exam result: {}"),
+    ("exam.bad_result", "考试结果的格式不正确: {}", "The exam result is malformed: {}"),
+    ("exam.no_key", "编译 clearning 时没有设置 CLEARNING_EXAM_KEY, 无法检查签名", "clearning was built without CLEARNING_EXAM_KEY, cannot check signatures"),
+    ("exam.no_signature", "{} 没有签名, 无法确认它没有被修改过", "{} is not signed, cannot tell whether it was changed"),
+    ("exam.bad_signature", "{} 的签名不正确, 考试结果被修改过", "The signature of {} is wrong, the result was changed"),
+    ("exam.verified", "签名正确: {} 得分 {} / {}", "Signature is valid: {} scored {} / {}"),
+    ("exam.late_result", "有练习在截止时间之后被修改过, 这些练习没有计分", "Some exercises were changed after the deadline and scored nothing"),
+    ("exam.changed_after_submit", "{} 交卷之后被修改过", "{} was changed after handing in"),
+    // watch.rs
+    ("watch.polling", "无法使用系统的文件通知 (可能是 'inotify limit' 达到了上限), 改为定时检查文件.", "File notifications are unavailable (maybe the 'inotify limit' was reached), polling for changes instead."),
+    ("watch.help", "输入 'hint' 查看提示 输入 'doc' 查看讲义 输入 'clear' 清屏 输入 'quit' 退出练习", "Type 'hint' for a hint, 'doc' for the notes, 'clear' to clear the screen or 'quit' to quit"),
+    ("watch.no_notes", "这个练习没有讲义", "This exercise has no notes"),
+    ("watch.bye", "希望你继续坚持鸭, 骚年！", "Keep it up, see you next time!"),
+    ("watch.stopped", "文件监视意外停止了", "The file watcher stopped unexpectedly"),
+    ("watch.unknown", "不认识的命令: {}", "unknown command: {}"),
+    ("watch.read_error", "无法读取命令: {}", "error reading command: {}"),
+    ("watch.error", "文件监视出错: {}", "watch error: {}"),
+    // dev.rs
+    ("dev.missing_file", "练习 {} 引用的文件 {} 不存在", "{} refers to the file {}, which does not exist"),
+    ("dev.no_test", "练习 {} 是 test 模式, 但是没有给出 test", "{} is in test mode but gives no test"),
+    ("dev.no_questions", "练习 {} 是 quiz 模式, 但是没有任何题目", "{} is in quiz mode but has no questions"),
+    ("dev.uncovered", "课程导图中有 {} 个主题还没有练习:", "{} topics of the course map have no exercises yet:"),
+    ("dev.ok", "info.toml 中的 {} 个练习都没有问题", "All {} exercises in info.toml are fine"),
+    ("dev.errors", "info.toml 中有 {} 个错误:", "info.toml has {} errors:"),
+    // chapter.rs
+    ("chapter.no_tag", "没有带 {} 标签的练习!", "No exercise is tagged {}!"),
+    ("chapter.not_found", "没找到 {} 这一章! 现在有这些章节:", "No chapter named {}! These are the chapters:"),
+    ("chapter.requires", "(需要先完成 {})", "(finish {} first)"),
+    // check.rs
+    ("check.required", "这个练习要求使用 `{}`, 但是代码中没有找到它", "This exercise requires `{}`, but it is not in your code"),
+    ("check.forbidden", "这个练习不允许使用 `{}`, 试着换一种写法", "This exercise does not allow `{}`, try another way"),
+    ("check.required_literal", "这个练习要求在字符串中使用 `{}`", "This exercise requires `{}` in a string"),
+    ("check.forbidden_literal", "不要直接在字符串中写出 `{}`, 试着让程序把它计算出来", "Do not write `{}` in a string directly, let the program compute it"),
+    ("check.line", "第 {} 行:", "line {}:"),
+    ("check.bad_regex", "info.toml 中的检查规则 `{}` 不是合法的正则表达式", "The check `{}` in info.toml is not a valid regular expression"),
+    // graph.rs
+    ("graph.unknown_requirement", "练习 {} 的前置练习 {} 不存在", "exercise {} requires {}, which does not exist"),
+    ("graph.cycle", "前置练习出现了循环: {}", "the required exercises form a cycle: {}"),
+    // leak.rs
+    ("leak.summary", "确定泄漏: {} 字节, 非法读取: {} 次, 非法写入: {} 次", "Definitely lost: {} bytes, invalid reads: {}, invalid writes: {}"),
+    ("leak.at", "在 {}", "at {}"),
+    ("leak.invalid_read", "非法读取: 读取了不属于你的 {} 字节内存", "Invalid read: read {} bytes of memory that is not yours"),
+    ("leak.invalid_write", "非法写入: 写入了不属于你的 {} 字节内存", "Invalid write: wrote {} bytes of memory that is not yours"),
+    ("leak.lost", "内存泄漏: malloc 分配的 {} 字节没有被 free", "Memory leak: {} bytes from malloc were never freed"),
+    // sanitizer.rs
+    ("sanitizer.stack_buffer_overflow", "数组下标越界: 访问了局部数组范围之外的内存", "Index out of bounds: accessed memory outside a local array"),
+    ("sanitizer.heap_buffer_overflow", "越界访问: 访问了 malloc 分配的内存范围之外的位置", "Out of bounds: accessed memory outside a malloc'd block"),
+    ("sanitizer.global_buffer_overflow", "数组下标越界: 访问了全局数组范围之外的内存", "Index out of bounds: accessed memory outside a global array"),
+    ("sanitizer.use_after_free", "使用了已经被 free 释放的内存", "Used memory that was already freed"),
+    ("sanitizer.double_free", "同一块内存被 free 了两次", "The same memory was freed twice"),
+    ("sanitizer.use_after_scope", "使用了已经离开作用域的局部变量 (例如返回了局部变量的地址)", "Used a local variable after its scope ended (e.g. returned the address of a local)"),
+    ("sanitizer.segv", "访问了非法的内存地址, 例如空指针或者没有初始化的指针", "Accessed an invalid address, e.g. through a null or uninitialized pointer"),
+    ("sanitizer.bad_free", "free 了不是由 malloc 分配的内存", "Freed memory that was not allocated by malloc"),
+    ("sanitizer.bad_access", "非法的内存访问", "Invalid memory access"),
+    ("sanitizer.out_of_bounds", "数组下标越界", "Index out of bounds"),
+    ("sanitizer.signed_overflow", "有符号整数溢出: 计算结果超出了类型能表示的范围", "Signed integer overflow: the result does not fit in the type"),
+    ("sanitizer.division_by_zero", "除数为 0", "Division by zero"),
+    ("sanitizer.null_pointer", "使用了空指针", "Used a null pointer"),
+    ("sanitizer.bad_shift", "移位的位数不合法 (为负数或者超过了类型的位数)", "Invalid shift amount (negative or wider than the type)"),
+    ("sanitizer.misaligned", "通过没有对齐的指针访问了内存", "Accessed memory through a misaligned pointer"),
+    ("sanitizer.undefined", "程序存在未定义行为", "The program has undefined behavior"),
+    // quiz.rs
+    ("quiz.single", "单选", "single choice"),
+    ("quiz.multiple", "多选", "multiple choice"),
+    ("quiz.blank", "填空", "fill in the blank"),
+    ("quiz.read_failed", "无法读取答案文件 {}: {}", "Cannot read the answer file {}: {}"),
+    ("quiz.prompt", "你的答案 (直接回车保留原来的答案 `{}`): ", "Your answer (press Enter to keep `{}`): "),
+    ("quiz.correct", "回答正确", "Correct"),
+    ("quiz.wrong", "回答错误", "Wrong"),
+    ("quiz.save_failed", "无法保存答案文件 {}: {}", "Cannot save the answer file {}: {}"),
+    ("quiz.all_correct", "{} 的题目全部答对了! 去掉答案文件中的 `I AM NOT DONE` 就可以做下一个练习了", "All answers in {} are correct! Remove `I AM NOT DONE` from the answer file to move on"),
+    ("quiz.not_all_correct", "{} 还有题目没有答对:\n", "Some answers in {} are still wrong:\n"),
+    ("quiz.how_to_fix", "修改答案文件 {} 后重新检验, 或者输入 `clearning quiz {}` 在终端中作答", "Edit the answer file {} and verify again, or answer in the terminal with `clearning quiz {}`"),
+    // doc.rs
+    ("doc.read_failed", "无法读取讲义 {}: {}", "Cannot read the notes {}: {}"),
+    // stats.rs
+    ("stats.save_failed", "无法保存学习记录 {}: {}", "Cannot save the study record {}: {}"),
+    ("stats.title", "学习记录 (在 watch 模式下做练习的时间):", "Study record (time spent in watch mode):"),
+    ("stats.minutes", "{} 分钟", "{} min"),
+    ("stats.estimated", "预计 {} 分钟", "estimated {} min"),
+    ("stats.total", "一共花了 {} 分钟, 预计需要 {} 分钟", "Spent {} minutes in total, {} minutes estimated"),
+    // harness.rs
+    ("harness.no_assertions", "隐藏测试没有输出任何断言结果", "The hidden tests did not report any checks"),
+    // map.rs
+    ("map.open_failed", "无法打开 {}: {}", "Cannot open {}: {}"),
+    ("map.not_xmind", "{} 不是合法的 XMind 文件: {}", "{} is not a valid XMind file: {}"),
+    ("map.read_failed", "无法读取 {} 中的 {}: {}", "Cannot read {} (entry {}): {}"),
+    ("map.no_content", "{} 中没有 content.json 或 content.xml", "{} has no content.json or content.xml"),
+    ("map.bad_content", "{} 格式错误: {}", "{} is malformed: {}"),
+    ("map.no_sheet", "{} 中没有画布", "{} has no sheet"),
+    // exercise.rs
+    ("exercise.uncategorized", "未分类", "Uncategorized"),
+    ("exercise.cached", "{} 没有修改, 使用了编译缓存", "{} is unchanged, using the build cache"),
+    ("exercise.quiz_not_compiled", "练习 {} 是一组题目, 不需要编译", "{} is a quiz and is not compiled"),
+    ("exercise.no_test", "info.toml 中没有给出练习 {} 的隐藏测试 (test)", "info.toml gives no hidden test (test) for {}"),
+    ("exercise.no_artifact", "make 运行成功了, 但是没有生成可执行文件 {}", "make succeeded, but did not build the executable {}"),
+    ("exercise.minutes", "{} 分钟", "{} min"),
+    ("exercise.input_failed", "无法打开输入文件 {}: {}", "Cannot open the input file {}: {}"),
+    ("exercise.timed_out", "\n程序运行超过了 {} 秒, 已经被终止. 是不是写出了死循环?\n", "\nThe program ran for more than {} seconds and was stopped. Is there an infinite loop?\n"),
+    // query.rs
+    ("query.parse_failed", "无法解析 {} 的语法树, 结构检查没有通过: {}", "Cannot parse {}, the structure checks did not pass: {}"),
+    ("query.no_function", "需要定义函数 `{}`", "Define the function `{}`"),
+    ("query.params", "函数 `{}` 的参数类型应该是 ({}), 现在是 ({})", "The parameters of `{}` should be ({}), but they are ({})"),
+    ("query.returns", "函数 `{}` 的返回值类型应该是 {}, 现在是 {}", "`{}` should return {}, but it returns {}"),
+    ("query.global", "这个练习不允许使用全局变量, 试着把 `{}` 放到函数里面", "This exercise does not allow global variables, move `{}` into a function"),
+    ("query.no_struct_named", "需要定义一个名为 `{}` 的结构体", "Define a struct named `{}`"),
+    ("query.no_union_named", "需要定义一个名为 `{}` 的联合体", "Define a union named `{}`"),
+    ("query.no_struct", "需要定义一个结构体", "Define a struct"),
+    ("query.no_union", "需要定义一个联合体", "Define a union"),
+    ("query.struct_fields", "结构体 `{}` 至少需要 {} 个成员", "The struct `{}` needs at least {} members"),
+    ("query.union_fields", "联合体 `{}` 至少需要 {} 个成员", "The union `{}` needs at least {} members"),
+    ("query.any_struct_fields", "至少需要一个有 {} 个成员的结构体", "Define a struct with at least {} members"),
+    ("query.any_union_fields", "至少需要一个有 {} 个成员的联合体", "Define a union with at least {} members"),
+    // config.rs
+    ("config.key.compiler", "编译练习使用的 C 编译器, 默认是 gcc", "C compiler used for the exercises, gcc by default"),
+    ("config.key.flags", "每次编译都会加上的编译参数, 例如 [\"-Wall\", \"-std=c11\"]", "Flags added to every compile, e.g. [\"-Wall\", \"-std=c11\"]"),
+    ("config.key.timeout", "练习程序最多运行多少秒, 超时会被终止, 默认不限制", "Seconds a program may run before it is stopped, no limit by default"),
+    ("config.key.lang", "界面语言, 例如 zh 或者 en", "Language of the interface, e.g. zh or en"),
+    ("config.key.color", "是否使用彩色输出", "Whether to use colored output"),
+    ("config.key.emoji", "是否使用 emoji", "Whether to use emoji"),
+    ("config.key.debounce", "watch 模式下文件修改后等待多少毫秒再检验, 默认是 2000", "Milliseconds watch mode waits after a change before verifying, 2000 by default"),
+    ("config.key.auto_advance", "通过练习后是否自动删除 I AM NOT DONE, 直接进入下一个练习", "Whether to remove I AM NOT DONE automatically and move on once an exercise passes"),
+    ("config.bad_file", "配置文件 {} 有错误: {}", "The settings file {} is invalid: {}"),
+    ("config.unknown_key", "没有这个配置项: {}, 输入 `clearning config list` 查看所有的配置项", "No such setting: {}, run `clearning config list` to see them all"),
+    ("config.unset", "(未设置)", "(not set)"),
+    ("config.course_file", "课程配置文件: {}", "Course settings file: {}"),
+    ("config.user_file", "用户配置文件: {}", "User settings file: {}"),
+    ("config.no_user_dir", "找不到用户的配置文件夹", "Cannot find the user settings folder"),
+    ("config.not_course", "{} 不是一个课程文件夹, 请在练习文件夹中运行, 或者加上 --global", "{} is not a course folder, run this inside the exercises folder or add --global"),
+    ("config.bad_value", "配置项的值不正确: {}", "Invalid value: {}"),
+    ("config.written", "已经写入 {}", "Saved to {}"),
+    ("config.write_failed", "无法写入配置文件: {}", "Cannot write the settings file: {}"),
+    // cache.rs
+    ("cache.cleaned", "已经清除了 {} 中的编译缓存", "Deleted the build cache in {}"),
+    ("cache.empty", "{} 中没有编译缓存", "There is no build cache in {}"),
+    ("cache.clean_failed", "无法清除编译缓存: {}", "Cannot delete the build cache: {}"),
+    // course.rs
+    ("course.dir_not_empty", "{} 已经存在并且不是空的, 请换一个文件夹", "{} already exists and is not empty, please pick another folder"),
+    ("course.read_failed", "无法读取课程: {}", "Cannot read the course: {}"),
+    ("course.no_manifest", "课程包中没有 {}, 它不是一个 clearning 课程", "The course pack has no {}, it is not a clearning course"),
+    ("course.init_failed", "无法创建练习文件夹: {}", "Cannot create the exercises folder: {}"),
+    ("course.created", "已经在 {} 中创建了练习", "Created the exercises in {}"),
+    ("course.next_steps", "输入下面的命令开始练习:", "Run these commands to start:"),
+    ("course.dir_without_manifest", "{} 不是一个课程: 里面没有 {}", "{} is not a course: it has no {}"),
+    ("course.not_found", "找不到课程 {}", "Cannot find the course {}"),
+    ("course.open_failed", "无法打开课程包 {}: {}", "Cannot open the course pack {}: {}"),
+    ("course.not_zip", "{} 不是一个课程包: 它不是 zip 压缩包", "{} is not a course pack: it is not a zip archive"),
+    ("course.archive_without_manifest", "{} 不是一个课程包: 里面没有 {}", "{} is not a course pack: it has no {}"),
+    ("course.archive_no_manifest", "课程包中没有 {}", "The course pack has no {}"),
+    // update.rs
+    ("update.bad_manifest", "{} 有错误: {}", "{} is invalid: {}"),
+    ("update.no_version", "(未标注)", "(unversioned)"),
+    ("update.not_course", "当前文件夹中没有 {}, 请在练习文件夹中运行 update", "There is no {} here, run update inside the exercises folder"),
+    ("update.up_to_date", "练习已经是最新的版本了: {}", "The exercises are already up to date: {}"),
+    ("update.failed", "无法更新练习: {}", "Cannot update the exercises: {}"),
+    ("update.updated", "练习已经更新到了版本 {}", "Updated the exercises to version {}"),
+    ("update.added", "新练习", "new"),
+    ("update.moved", "移动", "moved"),
+    ("update.removed", "已删除", "removed"),
+    ("update.removed_kept", "{} (文件 {} 保留了下来)", "{} (the file {} was kept)"),
+    ("update.summary", "更新了 {} 个文件, 保留了 {} 个你修改过的文件", "Updated {} files, kept {} files you changed"),
+    ("update.conflicts", "有 {} 个文件你和新版本的课程都修改过, 这些文件保留了你的版本:", "{} files were changed both by you and by the new course, your version was kept:"),
+    ("update.resolve", "请对照 .new 文件把新版本的修改合并到你的代码中, 然后删除 .new 文件", "Merge the changes from the .new files into your code, then delete the .new files"),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试消息的查找, 以及缺少翻译时退回中文
+    #[test]
+    fn test_message() {
+        assert_eq!(message_in(Lang::En, "verify.output"), "Output:");
+        assert_eq!(message_in(Lang::Zh, "verify.output"), "输出:");
+        assert_eq!(Lang::from_name("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(Lang::from_name("C"), None);
+        for (key, zh, en) in CATALOG {
+            assert_eq!(CATALOG.iter().filter(|(k, _, _)| k == key).count(), 1);
+            assert!(en.is_empty() || zh.matches("{}").count() == en.matches("{}").count());
+        }
+        assert_eq!(format("{} 编译失败: {}", &[&"a.c", &3]), "a.c 编译失败: 3");
+    }
+}
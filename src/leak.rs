@@ -0,0 +1,239 @@
+use console::style;
+use regex::Regex;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+// 在 valgrind 下运行练习时使用的参数
+pub const VALGRIND_ARGS: [&str; 3] = ["--leak-check=full", "--show-leak-kinds=definite", "--track-origins=yes"];
+
+// 没有 valgrind 时, 用 AddressSanitizer (自带 LeakSanitizer) 编译练习
+pub const LSAN_FLAGS: [&str; 3] = ["-g", "-fsanitize=address", "-fno-omit-frame-pointer"];
+
+// valgrind 报告中每一行开头的 ==pid==
+const VALGRIND_PREFIX_REGEX: &str = r"^==\d+== ?";
+// valgrind 的调用栈: at/by 0x地址: 函数 (文件:行)
+const VALGRIND_FRAME_REGEX: &str = r"^\s*(?:at|by) 0x[0-9A-Fa-f]+: (\S+) \((\S+?):(\d+)\)";
+// AddressSanitizer 的调用栈: #序号 0x地址 in 函数 文件:行
+const ASAN_FRAME_REGEX: &str = r"^\s*#\d+ 0x[0-9a-f]+ in (\S+) (\S+?):(\d+)";
+
+// 检查到的一个问题, 以及它在学生代码中的调用栈
+#[derive(PartialEq, Debug)]
+pub struct Problem {
+    // 给初学者看的描述
+    pub description: String,
+    // 只保留学生代码中的栈帧, 例如 "main (leak.c:3)"
+    pub frames: Vec<String>,
+}
+
+// valgrind memcheck 或 LeakSanitizer 的检查结果
+#[derive(PartialEq, Debug, Default)]
+pub struct LeakReport {
+    // 确定泄漏的字节数
+    pub definitely_lost: usize,
+    // 非法读取的次数
+    pub invalid_reads: usize,
+    // 非法写入的次数
+    pub invalid_writes: usize,
+    // 每一个问题的详细信息
+    pub problems: Vec<Problem>,
+}
+
+impl LeakReport {
+    pub fn passed(&self) -> bool {
+        self.definitely_lost == 0 && self.invalid_reads == 0 && self.invalid_writes == 0
+    }
+
+    // 输出汇总和每一个问题的调用栈
+    pub fn print(&self) {
+        println!(
+            "{}",
+            t!(
+                "leak.summary",
+                style(self.definitely_lost).red().bold(),
+                style(self.invalid_reads).red().bold(),
+                style(self.invalid_writes).red().bold()
+            )
+        );
+        println!();
+        for problem in &self.problems {
+            println!("{}", style(&problem.description).red());
+            for frame in &problem.frames {
+                println!("    {}", t!("leak.at", style(frame).blue()));
+            }
+            println!();
+        }
+    }
+}
+
+// 判断系统中是否安装了 valgrind, 只检查一次
+pub fn valgrind_exists() -> bool {
+    static EXISTS: OnceLock<bool> = OnceLock::new();
+    *EXISTS.get_or_init(|| {
+        Command::new("valgrind")
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    })
+}
+
+// 从程序的 stderr 中解析 valgrind 或 LeakSanitizer 的报告
+// sources 是学生的练习文件, 调用栈只保留这些文件中的栈帧
+pub fn parse(stderr: &str, sources: &[&Path]) -> LeakReport {
+    let prefix = Regex::new(VALGRIND_PREFIX_REGEX).unwrap();
+    if stderr.lines().any(|line| prefix.is_match(line) && line.contains("Memcheck")) {
+        parse_valgrind(stderr, sources)
+    } else {
+        parse_sanitizer(stderr, sources)
+    }
+}
+
+fn parse_valgrind(stderr: &str, sources: &[&Path]) -> LeakReport {
+    let prefix = Regex::new(VALGRIND_PREFIX_REGEX).unwrap();
+    let frame = Regex::new(VALGRIND_FRAME_REGEX).unwrap();
+    let invalid = Regex::new(r"^Invalid (read|write) of size (\d+)").unwrap();
+    let lost = Regex::new(r"^([\d,]+) bytes in [\d,]+ blocks are definitely lost").unwrap();
+    let summary = Regex::new(r"^\s*definitely lost: ([\d,]+) bytes").unwrap();
+
+    let mut report = LeakReport::default();
+    // 正在收集调用栈的问题, 遇到空行或者 "Address ..." 说明时结束
+    let mut collecting = false;
+    for line in stderr.lines().filter(|line| prefix.is_match(line)) {
+        let line = prefix.replace(line, "");
+        if let Some(caps) = invalid.captures(&line) {
+            let description = if &caps[1] == "read" {
+                report.invalid_reads += 1;
+                t!("leak.invalid_read", &caps[2])
+            } else {
+                report.invalid_writes += 1;
+                t!("leak.invalid_write", &caps[2])
+            };
+            report.problems.push(Problem { description, frames: Vec::new() });
+            collecting = true;
+        } else if let Some(caps) = lost.captures(&line) {
+            report.problems.push(Problem {
+                description: t!("leak.lost", &caps[1]),
+                frames: Vec::new(),
+            });
+            collecting = true;
+        } else if let Some(caps) = summary.captures(&line) {
+            report.definitely_lost = parse_number(&caps[1]);
+        } else if let (true, Some(caps)) = (collecting, frame.captures(&line)) {
+            push_frame(&mut report, sources, &caps[1], &caps[2], &caps[3]);
+        } else if line.trim().is_empty() || line.trim_start().starts_with("Address") {
+            collecting = false;
+        }
+    }
+    report
+}
+
+fn parse_sanitizer(stderr: &str, sources: &[&Path]) -> LeakReport {
+    let frame = Regex::new(ASAN_FRAME_REGEX).unwrap();
+    let access = Regex::new(r"^(READ|WRITE) of size (\d+)").unwrap();
+    let leak = Regex::new(r"^Direct leak of (\d+) byte\(s\)").unwrap();
+
+    let mut report = LeakReport::default();
+    let mut collecting = false;
+    for line in stderr.lines() {
+        if let Some(caps) = access.captures(line) {
+            let description = if &caps[1] == "READ" {
+                report.invalid_reads += 1;
+                t!("leak.invalid_read", &caps[2])
+            } else {
+                report.invalid_writes += 1;
+                t!("leak.invalid_write", &caps[2])
+            };
+            report.problems.push(Problem { description, frames: Vec::new() });
+            collecting = true;
+        } else if let Some(caps) = leak.captures(line) {
+            report.definitely_lost += parse_number(&caps[1]);
+            report.problems.push(Problem {
+                description: t!("leak.lost", &caps[1]),
+                frames: Vec::new(),
+            });
+            collecting = true;
+        } else if let (true, Some(caps)) = (collecting, frame.captures(line)) {
+            push_frame(&mut report, sources, &caps[1], &caps[2], &caps[3]);
+        } else if line.trim().is_empty() {
+            collecting = false;
+        }
+    }
+    report
+}
+
+// 只把学生代码中的栈帧加到最近的问题上
+fn push_frame(report: &mut LeakReport, sources: &[&Path], function: &str, file: &str, line: &str) {
+    let name = Path::new(file).file_name();
+    if !sources.iter().any(|source| source.file_name() == name) {
+        return;
+    }
+    if let Some(problem) = report.problems.last_mut() {
+        problem.frames.push(format!("{} ({}:{})", function, file, line));
+    }
+}
+
+// valgrind 会把大数字写成 1,024 的样子
+fn parse_number(number: &str) -> usize {
+    number.replace(',', "").parse().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试 valgrind 的报告
+    #[test]
+    fn test_parse_valgrind() {
+        let stderr = "\
+==12345== Memcheck, a memory error detector
+==12345== Invalid write of size 4
+==12345==    at 0x10916B: main (leak.c:4)
+==12345==  Address 0x4a4a068 is 0 bytes after a block of size 40 alloc'd
+==12345==    at 0x483B7F3: malloc (in /usr/lib/valgrind/vgpreload_memcheck-amd64-linux.so)
+==12345==    by 0x10915E: main (leak.c:3)
+==12345==
+==12345== 1,040 bytes in 1 blocks are definitely lost in loss record 1 of 1
+==12345==    at 0x483B7F3: malloc (in /usr/lib/valgrind/vgpreload_memcheck-amd64-linux.so)
+==12345==    by 0x109146: make (leak.c:2)
+==12345==    by 0x10915E: main (leak.c:3)
+==12345==
+==12345== LEAK SUMMARY:
+==12345==    definitely lost: 1,040 bytes in 1 blocks
+";
+        let report = parse(stderr, &[Path::new("exercises/leak.c")]);
+        assert_eq!(report.definitely_lost, 1040);
+        assert_eq!(report.invalid_reads, 0);
+        assert_eq!(report.invalid_writes, 1);
+        assert_eq!(report.problems[0].frames, vec!["main (leak.c:4)".to_string()]);
+        assert_eq!(
+            report.problems[1].frames,
+            vec!["make (leak.c:2)".to_string(), "main (leak.c:3)".to_string()]
+        );
+        assert!(!report.passed());
+    }
+
+    // 测试 LeakSanitizer 的报告
+    #[test]
+    fn test_parse_sanitizer() {
+        let stderr = "\
+==4495==ERROR: LeakSanitizer: detected memory leaks
+
+Direct leak of 40 byte(s) in 1 object(s) allocated from:
+    #0 0x7ffb7dcb89cf in __interceptor_malloc ../../../../src/libsanitizer/asan/asan_malloc_linux.cpp:69
+    #1 0x555fbeb19176 in make exercises/leak.c:2
+    #2 0x555fbeb19185 in main exercises/leak.c:4
+    #3 0x7ffb7da45249  (/lib/x86_64-linux-gnu/libc.so.6+0x27249)
+
+SUMMARY: AddressSanitizer: 40 byte(s) leaked in 1 allocation(s).
+";
+        let report = parse(stderr, &[Path::new("exercises/leak.c")]);
+        assert_eq!(report.definitely_lost, 40);
+        assert_eq!(
+            report.problems[0].frames,
+            vec!["make (exercises/leak.c:2)".to_string(), "main (exercises/leak.c:4)".to_string()]
+        );
+    }
+}
@@ -1,66 +1,268 @@
-use crate::exercise::{Exercise, ExerciseList};
+use crate::course::Course;
+use crate::exercise::{Exercise, ExerciseList, Input, Mode};
 use crate::run::run;
 use crate::verify::verify;
+use crate::watch::watch;
 use clap::{crate_version, App, Arg, SubCommand};
-use console::Emoji;
-use notify::DebouncedEvent;
-use notify::{RecommendedWatcher, RecursiveMode, Watcher};
-use std::ffi::OsStr;
+use console::{pad_str, Alignment};
 use std::fs;
-use std::io;
 use std::path::Path;
 use std::process::{Command, Stdio};
-use std::sync::mpsc::channel;
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
 
 #[macro_use]
 mod ui;
+#[macro_use]
+mod i18n;
 
+mod cache;
+mod chapter;
+mod check;
+mod config;
+mod course;
+mod doc;
+mod dev;
+mod exam;
 mod exercise;
+mod graph;
+mod harness;
+mod leak;
+mod map;
+mod query;
+mod quiz;
 mod run;
+mod sanitizer;
+mod stats;
+mod update;
 mod verify;
+mod watch;
 
 fn main() {
+    // 课程的根目录, 课程配置文件放在这里
+    // 课程参数的错误信息要等界面语言确定之后再输出, 所以这里先不检查课程
+    let course_arg = course::course_from_args();
+    let root = course_arg
+        .as_deref()
+        .map(Path::new)
+        .filter(|dir| dir.join(course::MANIFEST).is_file())
+        .unwrap_or(Path::new("."));
+    config::init(root);
+    i18n::init(
+        i18n::lang_from_args()
+            .as_deref()
+            .or(config::get().lang.as_deref()),
+    );
+    let course = Course::from_arg(course_arg.as_deref()).unwrap_or_else(|e| {
+        println!("{}", e);
+        std::process::exit(1)
+    });
+
     let matches = App::new("clearning")
         .version(crate_version!())
         .author("软院, 朕与将军解战袍")
-        .about("clearning 是一个 c语言小练习的合集.希望通过 clearning ,你能够对 c语言的语法有初步的了解,并且养成良好的代码风格.")
+        .about(t!("app.about"))
+        .arg(
+            Arg::with_name("lang")
+                .long("lang")
+                .takes_value(true)
+                .global(true)
+                .help(t!("arg.lang"))
+        )
+        .arg(
+            Arg::with_name("course")
+                .long("course")
+                .takes_value(true)
+                .global(true)
+                .value_name(t!("value.course"))
+                .help(t!("arg.course"))
+        )
         .arg(
             Arg::with_name("nocapture")
                 .long("nocapture")
-                .help("输出所测试练习的输出.(读起来有点绕, 不过你应该懂我意思吧?)")
+                .global(true)
+                .help(t!("arg.nocapture"))
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .global(true)
+                .conflicts_with("quiet")
+                .help(t!("arg.verbose"))
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .short("q")
+                .long("quiet")
+                .global(true)
+                .help(t!("arg.quiet"))
+        )
+        .arg(
+            Arg::with_name("sanitize")
+                .long("sanitize")
+                .global(true)
+                .help(t!("arg.sanitize"))
         )
         .subcommand(
             SubCommand::with_name("verify")
                 .alias("v")
-                .about("按照推荐顺序检验所有的练习")
+                .about(t!("cmd.verify"))
+                .arg(chapter_arg())
+                .arg(tag_arg())
         )
         .subcommand(
             SubCommand::with_name("watch")
                 .alias("w")
-                .about("每当当前练习文件被修改, 就会自动检验")
+                .about(t!("cmd.watch"))
+                .arg(chapter_arg())
+                .arg(tag_arg())
         )
         .subcommand(
             SubCommand::with_name("run")
                 .alias("r")
-                .about("运行单个练习文件")
+                .about(t!("cmd.run"))
+                .arg(Arg::with_name("name").required(true).index(1))
+                .arg(
+                    Arg::with_name("interactive")
+                        .short("i")
+                        .long("interactive")
+                        .conflicts_with("input")
+                        .help(t!("arg.interactive"))
+                )
+                .arg(
+                    Arg::with_name("input")
+                        .long("input")
+                        .takes_value(true)
+                        .value_name(t!("value.file"))
+                        .help(t!("arg.input"))
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("quiz")
+                .alias("q")
+                .about(t!("cmd.quiz"))
                 .arg(Arg::with_name("name").required(true).index(1)),
         )
         .subcommand(
             SubCommand::with_name("hint")
                 .alias("h")
-                .about("给出当前练习的提示")
+                .about(t!("cmd.hint"))
                 .arg(Arg::with_name("name").required(true).index(1)),
         )
+        .subcommand(
+            SubCommand::with_name("doc")
+                .alias("d")
+                .about(t!("cmd.doc"))
+                .arg(Arg::with_name("topic").index(1)),
+        )
+        .subcommand(
+            SubCommand::with_name("map")
+                .alias("m")
+                .about(t!("cmd.map"))
+                .arg(
+                    Arg::with_name("depth")
+                        .long("depth")
+                        .takes_value(true)
+                        .help(t!("arg.depth"))
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("dev")
+                .about(t!("cmd.dev"))
+                .subcommand(
+                    SubCommand::with_name("check")
+                        .about(t!("cmd.dev.check"))
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("init")
+                .about(t!("cmd.init"))
+                .arg(
+                    Arg::with_name("dir")
+                        .index(1)
+                        .default_value("clearning")
+                        .help(t!("arg.dir"))
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("exam")
+                .about(t!("cmd.exam"))
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .required_unless("verify")
+                        .help(t!("arg.exam"))
+                )
+                .arg(
+                    Arg::with_name("verify")
+                        .long("verify")
+                        .takes_value(true)
+                        .value_name(t!("value.file"))
+                        .help(t!("arg.exam_verify"))
+                ),
+        )
+        .subcommand(SubCommand::with_name("update").about(t!("cmd.update")))
+        .subcommand(
+            SubCommand::with_name("cache")
+                .about(t!("cmd.cache"))
+                .subcommand(
+                    SubCommand::with_name("clean")
+                        .about(t!("cmd.cache.clean"))
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("config")
+                .about(t!("cmd.config"))
+                .subcommand(
+                    SubCommand::with_name("get")
+                        .about(t!("cmd.config.get"))
+                        .arg(Arg::with_name("key").required(true).index(1))
+                )
+                .subcommand(
+                    SubCommand::with_name("set")
+                        .about(t!("cmd.config.set"))
+                        .arg(Arg::with_name("key").required(true).index(1))
+                        .arg(Arg::with_name("value").required(true).index(2))
+                        .arg(
+                            Arg::with_name("global")
+                                .long("global")
+                                .help(t!("arg.global"))
+                        )
+                )
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about(t!("cmd.config.list"))
+                ),
+        )
         .subcommand(
             SubCommand::with_name("list")
                 .alias("l")
-                .about("列出当前所有的练习")
+                .about(t!("cmd.list"))
+                .arg(tag_arg())
+                .arg(
+                    Arg::with_name("difficulty")
+                        .long("difficulty")
+                        .takes_value(true)
+                        .help(t!("arg.difficulty"))
+                )
+                .arg(
+                    Arg::with_name("sort")
+                        .long("sort")
+                        .takes_value(true)
+                        .possible_values(&["difficulty", "time", "name"])
+                        .help(t!("arg.sort"))
+                )
         )
         .get_matches();
     
+    let verbosity = if matches.is_present("verbose") {
+        ui::Verbosity::Verbose
+    } else if matches.is_present("quiet") {
+        ui::Verbosity::Quiet
+    } else {
+        ui::Verbosity::Normal
+    };
+    ui::init(verbosity, matches.is_present("nocapture"));
+
     if matches.subcommand_name().is_none() {
         println!();
         println!(r#"                 welcome to ...                       "#);
@@ -75,72 +277,276 @@ fn main() {
         println!();
     }
 
-    if !Path::new("info.toml").exists() {
+    if let Some(matches) = matches.subcommand_matches("config") {
+        let result = match matches.subcommand() {
+            ("get", Some(matches)) => config::print_value(matches.value_of("key").unwrap()),
+            ("set", Some(matches)) => config::set(
+                matches.value_of("key").unwrap(),
+                matches.value_of("value").unwrap(),
+                matches.is_present("global"),
+                root,
+            ),
+            _ => {
+                config::list();
+                Ok(())
+            }
+        };
+        std::process::exit(if result.is_ok() { 0 } else { 1 });
+    }
+
+    if let Some(matches) = matches.subcommand_matches("init") {
+        let dir = Path::new(matches.value_of("dir").unwrap());
+        course::init(&course, dir).unwrap_or_else(|_| std::process::exit(1));
+        return;
+    }
+
+    // update 在练习文件夹中运行, --course 指定的是新版本的课程
+    if matches.subcommand_matches("update").is_some() {
+        update::update(&course).unwrap_or_else(|_| std::process::exit(1));
+        return;
+    }
+
+    match &course {
+        Course::Embedded => {}
+        Course::Dir(dir) => {
+            if let Err(e) = std::env::set_current_dir(dir) {
+                println!("{}: {}", dir.display(), e);
+                std::process::exit(1);
+            }
+        }
+        Course::Archive(archive) => {
+            println!(
+                "{}",
+                t!("main.archive_needs_init", archive.display(), archive.display())
+            );
+            std::process::exit(1);
+        }
+    }
+
+    if !Path::new(course::MANIFEST).exists() {
+        if matches.subcommand_name().is_none() {
+            println!("{}", course::welcome());
+            println!("{}", t!("main.start_with_init"));
+            return;
+        }
         println!(
-            "请在 clearning 文件夹中打开 {}",
-            std::env::current_exe().unwrap().to_str().unwrap()
+            "{}",
+            t!("main.open_in_folder", std::env::current_exe().unwrap().display())
         );
-        println!("试着输入命令 `cd clearning/`!");
+        println!("{}", t!("main.try_cd"));
         std::process::exit(1);
     }
 
     if !gcc_exists() {
-        println!("找不到 gcc 编译器.");
-        println!("试着输入命令 `gcc --version` 看看有什么问题.");
-        println!("百度一下: 怎么安装 gcc");
+        println!("{}", t!("main.no_gcc", config::get().compiler()));
+        println!("{}", t!("main.gcc_version", config::get().compiler()));
+        println!("{}", t!("main.install_gcc"));
         std::process::exit(1);
     }
 
-    let toml_str = &fs::read_to_string("info.toml").unwrap();
-    let exercises = toml::from_str::<ExerciseList>(toml_str).unwrap().exercises;
+    let toml_str = &fs::read_to_string(course::MANIFEST).unwrap();
+    let list = toml::from_str::<ExerciseList>(toml_str).unwrap();
+    let mut exercises = list.exercises;
+
+    if let Err(e) = graph::validate(&exercises) {
+        println!("{}", t!("main.bad_info", e));
+        std::process::exit(1);
+    }
+    if let Some(e) = exercises
+        .iter()
+        .find(|e| e.difficulty.is_some_and(|d| !(1..=5).contains(&d)))
+    {
+        println!("{}", t!("main.bad_info", t!("main.bad_difficulty", e.name)));
+        std::process::exit(1);
+    }
+    if let Some(e) = exercises
+        .iter()
+        .enumerate()
+        .find(|(i, e)| exercises[..*i].iter().any(|other| other.id() == e.id()))
+        .map(|(_, e)| e)
+    {
+        println!("{}", t!("main.bad_info", t!("main.duplicate_id", e.id())));
+        std::process::exit(1);
+    }
+
+    stats::migrate(&exercises);
+
+    if matches.is_present("sanitize") {
+        exercises.iter_mut().for_each(|e| e.sanitize = true);
+    }
+
+    if let Some(matches) = matches.subcommand_matches("exam") {
+        let result = match matches.value_of("verify") {
+            Some(path) => exam::verify(Path::new(path)),
+            None => exam::exam(&list.exams, &exercises, matches.value_of("name").unwrap()),
+        };
+        result.unwrap_or_else(|_| std::process::exit(1));
+        return;
+    }
+
+    let chapter = matches
+        .subcommand_matches("verify")
+        .or_else(|| matches.subcommand_matches("watch"))
+        .and_then(|matches| matches.value_of("chapter"));
+    if let Some(chapter) = chapter {
+        chapter::retain(&mut exercises, chapter);
+    }
+
+    let tag = matches
+        .subcommand_matches("verify")
+        .or_else(|| matches.subcommand_matches("watch"))
+        .and_then(|matches| matches.value_of("tag"));
+    if let Some(tag) = tag {
+        chapter::retain_tag(&mut exercises, tag);
+    }
 
-    if matches.subcommand_matches("list").is_some() {
-        exercises.iter().for_each(|e| println!("{}", e.name));
+    if let Some(matches) = matches.subcommand_matches("list") {
+        // 解锁状态根据全部练习计算, 筛选只决定列出哪些练习
+        let done = graph::done(&exercises);
+        let tag = matches.value_of("tag");
+        let difficulty = matches.value_of("difficulty").map(|difficulty| {
+            difficulty.parse::<u8>().unwrap_or_else(|_| {
+                println!("{}", t!("main.bad_difficulty_arg"));
+                std::process::exit(1)
+            })
+        });
+        let shown: Vec<&Exercise> = exercises
+            .iter()
+            .filter(|e| tag.map_or(true, |tag| chapter::has_tag(e, tag)))
+            .filter(|e| difficulty.map_or(true, |d| e.difficulty == Some(d)))
+            .collect();
+        if let (Some(tag), true) = (tag, shown.is_empty()) {
+            chapter::no_tagged(tag);
+        }
+        match matches.value_of("sort") {
+            Some(key) => chapter::list_sorted(&shown, &done, key),
+            None => chapter::list(&shown, &exercises, &done),
+        }
     }
-    if let Some(ref matches) = matches.subcommand_matches("run") {
+    if let Some(matches) = matches.subcommand_matches("run") {
         let name = matches.value_of("name").unwrap();
 
         let matching_exercise = |e: &&Exercise| name == e.name;
 
         let exercise = exercises.iter().find(matching_exercise).unwrap_or_else(|| {
-            println!("没找到这个练习! 看看是不是输错名字了!");
+            println!("{}", t!("main.no_exercise"));
             std::process::exit(1)
         });
 
-        run(&exercise).unwrap_or_else(|_| std::process::exit(1));
+        let missing = graph::missing(exercise, &exercises, &graph::done(&exercises));
+        if !missing.is_empty() {
+            println!("{}", t!("main.locked", missing.join(", ")));
+        }
+
+        let input = match matches.value_of("input") {
+            Some(path) => Input::File(Path::new(path)),
+            None if matches.is_present("interactive") => Input::Terminal,
+            None => Input::Null,
+        };
+        run(exercise, input).unwrap_or_else(|_| std::process::exit(1));
     }
 
-    if let Some(ref matches) = matches.subcommand_matches("hint") {
+    if let Some(matches) = matches.subcommand_matches("quiz") {
+        let name = matches.value_of("name").unwrap();
+
+        let exercise = exercises
+            .iter()
+            .find(|e| name == e.name && e.mode == Mode::Quiz)
+            .unwrap_or_else(|| {
+                println!("{}", t!("main.no_quiz"));
+                std::process::exit(1)
+            });
+
+        quiz::quiz(exercise).unwrap_or_else(|_| std::process::exit(1));
+    }
+
+    if let Some(matches) = matches.subcommand_matches("hint") {
         let name = matches.value_of("name").unwrap();
 
         let exercise = exercises
             .iter()
             .find(|e| name == e.name)
             .unwrap_or_else(|| {
-                println!("没找到这个练习! 看看是不是输错名字了!");
+                println!("{}", t!("main.no_exercise"));
                 std::process::exit(1)
             });
 
-        println!("{}", exercise.hint);
+        if exam::in_progress() {
+            println!("{}", t!("exam.hint_locked"));
+            std::process::exit(1);
+        }
+        println!("{}", exercise.hint.text());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("doc") {
+        let topics = doc::topics(&exercises);
+        match matches.value_of("topic") {
+            Some(topic) => {
+                let (_, path) = topics.iter().find(|(name, _)| name == topic).unwrap_or_else(|| {
+                    println!("{}", t!("main.no_doc"));
+                    std::process::exit(1)
+                });
+                doc::show(path).unwrap_or_else(|_| std::process::exit(1));
+            }
+            None => topics
+                .iter()
+                .for_each(|(name, path)| println!("{}  ({})", name, path.display())),
+        }
+    }
+
+    if let Some(matches) = matches.subcommand_matches("map") {
+        let depth = matches.value_of("depth").map(|depth| {
+            depth.parse().unwrap_or_else(|_| {
+                println!("{}", t!("main.bad_depth"));
+                std::process::exit(1)
+            })
+        });
+        let root = map::load(Path::new(map::COURSE_MAP)).unwrap_or_else(|e| {
+            println!("{}", e);
+            std::process::exit(1)
+        });
+        map::print(&root, &exercises, &graph::done(&exercises), depth);
+    }
+
+    if let Some(matches) = matches.subcommand_matches("cache") {
+        if matches.subcommand_matches("clean").is_some() {
+            cache::clean().unwrap_or_else(|_| std::process::exit(1));
+        }
+    }
+
+    if let Some(matches) = matches.subcommand_matches("dev") {
+        if matches.subcommand_matches("check").is_some() {
+            dev::check(&exercises).unwrap_or_else(|_| std::process::exit(1));
+        }
     }
 
     if matches.subcommand_matches("verify").is_some() {
         verify(&exercises).unwrap_or_else(|_| std::process::exit(1));
+        stats::print_summary(&exercises);
     }
 
     if matches.subcommand_matches("watch").is_some() {
         if let Err(e) = watch(&exercises) {
-            println!("Error: 无法监视你的程序. 错误信息: {:?}.", e);
-            println!("很可能是因为你磁盘内存满了 或者 你的 'inotify limit' 达到了上限.");
+            println!("{}", t!("main.watch_error", format!("{:?}", e)));
+            println!("{}", t!("main.watch_error_reason"));
             std::process::exit(1);
         }
+        if let Some(chapter) = chapter {
+            println!(
+                "{emoji} {} {emoji}",
+                t!("main.chapter_done", chapter),
+                emoji = ui::emoji("🎉", "★")
+            );
+            return;
+        }
         println!(
-            "{emoji} 恭喜你完成了所有的练习! {emoji}",
-            emoji = Emoji("🎉", "★")
+            "{emoji} {} {emoji}",
+            t!("main.all_done"),
+            emoji = ui::emoji("🎉", "★")
         );
         println!();
         println!("+--------------------------------------------------------+");     
-        println!("|                    千里之行,始于足下                    |");       
+        println!("|{}|", pad_str(t!("main.motto"), 56, Alignment::Center, None));       
         println!("+--------------------------------------------------------+");       
         println!("+＃＃＃＃＃＃＃＃＃＃＃＃＃ＬｆｆＬ＃＃＃＃＃＃＃＃＃＃＃＃＃+");
         println!("+＃＃＃＃＃＃＃＃＃＃＃＃ＬｆｆｆｆＬ＃＃＃＃＃＃＃＃＃＃＃＃+");
@@ -177,94 +583,40 @@ fn main() {
         println!("+＃＃＃＃＃＃＃＃＃＃＃＃，，，，，，＃＃＃＃＃＃＃＃＃＃＃＃+");
         println!("+＃＃＃＃＃＃＃＃＃＃＃＃＃；，，；＃＃＃＃＃＃＃＃＃＃＃＃＃+");
         println!();
-        println!("希望通过 clearining 的练习，你对 C语言有了初步的了解.");
-        println!("如果想要更加深入地了解 C语言 或者 C++, 你可以复制下方链接, 到浏览器进行浏览:");
-        println!("https://zh.cppreference.com/");
+        println!("{}", t!("main.farewell"));
+        println!("{}", t!("main.learn_more"));
+        println!("{}", t!("main.reference"));
         println!();
+        stats::print_summary(&exercises);
     }
 
     if matches.subcommand_name().is_none() {
-        let text = fs::read_to_string("default_out.txt").unwrap();
-        println!("{}", text);
+        println!("{}", course::welcome());
     }
 
 }
 
-fn spawn_watch_shell(failed_exercise_hint: &Arc<Mutex<Option<String>>>) {
-    let failed_exercise_hint = Arc::clone(failed_exercise_hint);
-    println!("输入 'hint' 查看提示 输入 'clear' 清屏 输入 'quit' 退出练习");
-    thread::spawn(move || loop {
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => {
-                let input = input.trim();
-                if input.eq("hint") {
-                    if let Some(hint) = &*failed_exercise_hint.lock().unwrap() {
-                        println!("{}", hint);
-                    }
-                } else if input.eq("clear") {
-                    println!("\x1B[2J\x1B[1;1H");
-                } else if input.eq("quit") {
-                    println!("希望你继续坚持鸭, 骚年！");
-                    std::process::exit(0);
-                } else {
-                    println!("unknown command: {}", input);
-                }
-            }
-            Err(error) => println!("error reading command: {}", error),
-        }
-    });
+// verify 和 watch 的 --chapter 参数
+fn chapter_arg() -> Arg<'static, 'static> {
+    Arg::with_name("chapter")
+        .long("chapter")
+        .takes_value(true)
+        .value_name(t!("value.chapter"))
+        .help(t!("arg.chapter"))
 }
 
-fn watch(exercises: &[Exercise]) -> notify::Result<()> {
-    /* Clears the terminal with an ANSI escape code.
-    Works in UNIX and newer Windows terminals. */
-    fn clear_screen() {
-        println!("\x1Bc");
-    }
-
-    let (tx, rx) = channel();
-
-    let mut watcher: RecommendedWatcher = Watcher::new(tx, Duration::from_secs(2))?;
-    watcher.watch(Path::new("./exercises"), RecursiveMode::Recursive)?;
-
-    clear_screen();
-
-    let to_owned_hint = |t: &Exercise| t.hint.to_owned();
-    let failed_exercise_hint = match verify(exercises.iter()) {
-        Ok(_) => return Ok(()),
-        Err(exercise) => Arc::new(Mutex::new(Some(to_owned_hint(exercise)))),
-    };
-    spawn_watch_shell(&failed_exercise_hint);
-    loop {
-        match rx.recv() {
-            Ok(event) => match event {
-                DebouncedEvent::Create(b) | DebouncedEvent::Chmod(b) | DebouncedEvent::Write(b) => {
-                    if b.extension() == Some(OsStr::new("c")) && b.exists() {
-                        let filepath = b.as_path().canonicalize().unwrap();
-                        let pending_exercises = exercises
-                            .iter()
-                            .skip_while(|e| !filepath.ends_with(&e.path));
-                        clear_screen();
-                        match verify(pending_exercises) {
-                            Ok(_) => return Ok(()),
-                            Err(exercise) => {
-                                let mut failed_exercise_hint = failed_exercise_hint.lock().unwrap();
-                                *failed_exercise_hint = Some(to_owned_hint(exercise));
-                            }
-                        }
-                    }
-                }
-                _ => {}
-            },
-            Err(e) => println!("watch error: {:?}", e),
-        }
-    }
+// verify、watch 和 list 的 --tag 参数
+fn tag_arg() -> Arg<'static, 'static> {
+    Arg::with_name("tag")
+        .long("tag")
+        .takes_value(true)
+        .value_name(t!("value.tag"))
+        .help(t!("arg.tag"))
 }
 
 fn gcc_exists() -> bool {
-    Command::new("gcc")
-        .args(&["--version"])
+    Command::new(config::get().compiler())
+        .args(["--version"])
         .stdout(Stdio::null())
         .spawn()
         .and_then(|mut child| child.wait())
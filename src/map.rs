@@ -0,0 +1,296 @@
+use crate::exercise::Exercise;
+use console::style;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// 课程所依据的思维导图
+pub const COURSE_MAP: &str = "C语言基础.xmind";
+
+// 思维导图中的一个主题
+#[derive(PartialEq, Debug, Default)]
+pub struct Topic {
+    pub title: String,
+    pub children: Vec<Topic>,
+}
+
+// 读取 XMind 文件中第一张画布的主题树
+// XMind 文件是一个 zip 压缩包, 新版本把内容存在 content.json 中, 旧版本存在 content.xml 中
+pub fn load(path: &Path) -> Result<Topic, String> {
+    let file = File::open(path).map_err(|e| t!("map.open_failed", path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| t!("map.not_xmind", path.display(), e))?;
+    for (name, parse) in [
+        (
+            "content.json",
+            parse_json as fn(&str) -> Result<Topic, String>,
+        ),
+        ("content.xml", parse_xml),
+    ]
+    .iter()
+    {
+        if let Ok(mut entry) = archive.by_name(name) {
+            let mut content = String::new();
+            entry
+                .read_to_string(&mut content)
+                .map_err(|e| t!("map.read_failed", path.display(), name, e))?;
+            return parse(&content);
+        }
+    }
+    Err(t!("map.no_content", path.display()))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonSheet {
+    root_topic: JsonTopic,
+}
+
+#[derive(Deserialize)]
+struct JsonTopic {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    children: Option<JsonChildren>,
+}
+
+#[derive(Deserialize)]
+struct JsonChildren {
+    #[serde(default)]
+    attached: Vec<JsonTopic>,
+}
+
+impl From<JsonTopic> for Topic {
+    fn from(topic: JsonTopic) -> Topic {
+        Topic {
+            title: topic.title,
+            children: topic
+                .children
+                .map(|children| children.attached.into_iter().map(Topic::from).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+fn parse_json(content: &str) -> Result<Topic, String> {
+    let sheets: Vec<JsonSheet> =
+        serde_json::from_str(content).map_err(|e| t!("map.bad_content", "content.json", e))?;
+    sheets
+        .into_iter()
+        .next()
+        .map(|sheet| sheet.root_topic.into())
+        .ok_or_else(|| t!("map.no_sheet", "content.json"))
+}
+
+fn parse_xml(content: &str) -> Result<Topic, String> {
+    let document =
+        roxmltree::Document::parse(content).map_err(|e| t!("map.bad_content", "content.xml", e))?;
+    let root = document
+        .descendants()
+        .find(|node| node.has_tag_name("sheet"))
+        .and_then(|sheet| sheet.children().find(|node| node.has_tag_name("topic")))
+        .ok_or_else(|| t!("map.no_sheet", "content.xml"))?;
+    Ok(xml_topic(root))
+}
+
+// <topic><title>..</title><children><topics type="attached"><topic>..</topic></topics></children></topic>
+fn xml_topic(node: roxmltree::Node) -> Topic {
+    let title = child(node, "title")
+        .first()
+        .and_then(|title| title.text())
+        .unwrap_or("")
+        .to_string();
+    let children = child(node, "children")
+        .into_iter()
+        .flat_map(|children| child(children, "topics"))
+        .filter(|topics| topics.attribute("type") == Some("attached"))
+        .flat_map(|topics| child(topics, "topic"))
+        .map(xml_topic)
+        .collect();
+    Topic { title, children }
+}
+
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    tag: &str,
+) -> Vec<roxmltree::Node<'a, 'input>> {
+    node.children()
+        .filter(|child| child.has_tag_name(tag))
+        .collect()
+}
+
+// 练习的名字或者标签和主题相同时, 这个练习属于这个主题
+fn covers(exercise: &Exercise, title: &str) -> bool {
+    exercise.name == title || exercise.tags.iter().any(|tag| tag == title)
+}
+
+impl Topic {
+    // 这个主题以及它所有子主题的练习, 每个练习只出现一次
+    fn exercises<'a>(&self, exercises: &'a [Exercise]) -> Vec<&'a Exercise> {
+        let mut found = Vec::new();
+        self.collect(exercises, &mut found);
+        found
+    }
+
+    fn collect<'a>(&self, exercises: &'a [Exercise], found: &mut Vec<&'a Exercise>) {
+        for exercise in exercises.iter().filter(|e| covers(e, &self.title)) {
+            if !found.iter().any(|f| std::ptr::eq(*f, exercise)) {
+                found.push(exercise);
+            }
+        }
+        for child in &self.children {
+            child.collect(exercises, found);
+        }
+    }
+}
+
+// 以树的形式输出主题, 以及每个主题中练习的完成情况
+// depth 限制输出的层数, 更深的主题只计入进度
+pub fn print(root: &Topic, exercises: &[Exercise], done: &HashSet<&str>, depth: Option<usize>) {
+    print_topic(root, exercises, done, "", "", depth);
+}
+
+fn print_topic(
+    topic: &Topic,
+    exercises: &[Exercise],
+    done: &HashSet<&str>,
+    prefix: &str,
+    branch: &str,
+    depth: Option<usize>,
+) {
+    let found = topic.exercises(exercises);
+    let finished = found
+        .iter()
+        .filter(|e| done.contains(e.id()))
+        .count();
+    let progress = format!("({}/{})", finished, found.len());
+    let progress = if found.is_empty() {
+        style(progress).dim()
+    } else if finished == found.len() {
+        style(progress).green()
+    } else {
+        style(progress).blue()
+    };
+    println!("{}{}{} {}", prefix, branch, topic.title, progress);
+
+    if depth == Some(0) {
+        return;
+    }
+    let prefix = match branch {
+        "" => String::new(),
+        "└─ " => format!("{}   ", prefix),
+        _ => format!("{}│  ", prefix),
+    };
+    for (index, child) in topic.children.iter().enumerate() {
+        let branch = if index + 1 == topic.children.len() {
+            "└─ "
+        } else {
+            "├─ "
+        };
+        print_topic(
+            child,
+            exercises,
+            done,
+            &prefix,
+            branch,
+            depth.map(|depth| depth - 1),
+        );
+    }
+}
+
+// 找出没有任何练习的主题, 例如 "C语言基础 / 表达式 / 运算符"
+// 一个主题没有练习时, 不再列出它的子主题
+pub fn uncovered(root: &Topic, exercises: &[Exercise]) -> Vec<String> {
+    fn visit(topic: &Topic, exercises: &[Exercise], path: &str, found: &mut Vec<String>) {
+        let path = if path.is_empty() {
+            topic.title.clone()
+        } else {
+            format!("{} / {}", path, topic.title)
+        };
+        if topic.exercises(exercises).is_empty() {
+            found.push(path);
+            return;
+        }
+        for child in &topic.children {
+            visit(child, exercises, &path, found);
+        }
+    }
+
+    let mut found = Vec::new();
+    visit(root, exercises, "", &mut found);
+    found
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn topic(title: &str, children: Vec<Topic>) -> Topic {
+        Topic {
+            title: title.to_string(),
+            children,
+        }
+    }
+
+    // 测试旧版本 XMind 的 content.xml
+    #[test]
+    fn test_parse_xml() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xmap-content xmlns="urn:xmind:xmap:xmlns:content:2.0"><sheet id="1"><topic id="2"><title>C语言基础</title>
+<children><topics type="attached"><topic id="3"><title>声明</title></topic>
+<topic id="4"><title>表达式</title><children><topics type="attached"><topic id="5"><title>&amp;=</title></topic></topics>
+<topics type="detached"><topic id="6"><title>浮动主题</title></topic></topics></children></topic>
+</topics></children></topic></sheet></xmap-content>"#;
+        assert_eq!(
+            parse_xml(content).unwrap(),
+            topic(
+                "C语言基础",
+                vec![
+                    topic("声明", vec![]),
+                    topic("表达式", vec![topic("&=", vec![])])
+                ]
+            )
+        );
+    }
+
+    // 测试新版本 XMind 的 content.json
+    #[test]
+    fn test_parse_json() {
+        let content = r#"[{"id":"1","rootTopic":{"id":"2","title":"C语言基础",
+            "children":{"attached":[{"id":"3","title":"声明"}]}}}]"#;
+        assert_eq!(
+            parse_json(content).unwrap(),
+            topic("C语言基础", vec![topic("声明", vec![])])
+        );
+    }
+
+    // 测试按照名字和标签把练习对应到主题, 并找出没有练习的主题
+    #[test]
+    fn test_uncovered() {
+        let root = topic(
+            "C语言基础",
+            vec![
+                topic("声明", vec![topic("基础类型的声明", vec![])]),
+                topic("表达式", vec![topic("运算符", vec![])]),
+                topic("int01", vec![]),
+            ],
+        );
+        let exercises = vec![
+            Exercise {
+                name: "int01".to_string(),
+                ..Default::default()
+            },
+            Exercise {
+                name: "基础类型变量声明01".to_string(),
+                tags: vec!["基础类型的声明".to_string()],
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            uncovered(&root, &exercises),
+            vec!["C语言基础 / 表达式".to_string()]
+        );
+    }
+}
@@ -0,0 +1,391 @@
+use crate::check::Violation;
+use crate::config;
+use lang_c::ast::{
+    DeclarationSpecifier, Declarator, DeclaratorKind, DerivedDeclarator, ExternalDeclaration,
+    StorageClassSpecifier, StructDeclaration, StructKind, StructType, TypeQualifier,
+    TypeSpecifier,
+};
+use lang_c::driver::{self, Config, Parse};
+use lang_c::loc;
+use lang_c::span::{Node, Span};
+use lang_c::visit::{self, Visit};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+// info.toml 中声明的结构化查询, 在学生代码的语法树上检查
+#[derive(Deserialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Query {
+    // 定义了一个结构体 (可以指定名字), 并且至少有 min_fields 个成员
+    Struct {
+        name: Option<String>,
+        #[serde(default)]
+        min_fields: usize,
+    },
+    // 定义了一个联合体 (可以指定名字), 并且至少有 min_fields 个成员
+    Union {
+        name: Option<String>,
+        #[serde(default)]
+        min_fields: usize,
+    },
+    // 定义了函数 name, 并且参数和返回值的类型符合要求, 例如 params = ["int", "int"]
+    Function {
+        name: String,
+        params: Option<Vec<String>>,
+        returns: Option<String>,
+    },
+    // 不允许定义全局变量
+    NoGlobals,
+}
+
+// 从学生代码中收集到的定义
+#[derive(Default, Debug)]
+struct Facts {
+    // 结构体和联合体: (是否是联合体, 名字, 成员个数)
+    records: Vec<(bool, Option<String>, usize)>,
+    // 函数定义: (名字, 返回值类型, 参数类型, 行号)
+    functions: Vec<(String, String, Vec<String>, usize)>,
+    // 全局变量: (名字, 行号)
+    globals: Vec<(String, usize)>,
+}
+
+// 解析源文件, 并检查所有的查询
+// 预处理使用配置中的编译器, 并加上练习的头文件目录
+pub fn run(queries: &[Query], path: &Path, include_dirs: &[PathBuf]) -> Vec<Violation> {
+    let mut cpp = Config::with_gcc();
+    cpp.cpp_command = config::get().compiler().to_string();
+    cpp.cpp_options
+        .extend(include_dirs.iter().map(|dir| format!("-I{}", dir.display())));
+    let parse = match driver::parse(&cpp, path) {
+        Ok(parse) => parse,
+        // 无法解析时结构检查不能算通过
+        Err(e) => {
+            return vec![Violation {
+                message: t!("query.parse_failed", path.display(), e),
+                line: None,
+            }]
+        }
+    };
+    let facts = collect(&parse, path);
+    queries.iter().flat_map(|query| check(query, &facts)).collect()
+}
+
+fn check(query: &Query, facts: &Facts) -> Vec<Violation> {
+    let violation = |message: String, line: Option<usize>| vec![Violation { message, line }];
+    match query {
+        Query::Struct { name, min_fields } => check_record(false, name, *min_fields, facts),
+        Query::Union { name, min_fields } => check_record(true, name, *min_fields, facts),
+        Query::Function {
+            name,
+            params,
+            returns,
+        } => {
+            let (_, ret, actual, line) = match facts.functions.iter().find(|f| &f.0 == name) {
+                Some(function) => function,
+                None => return violation(t!("query.no_function", name), None),
+            };
+            let mut violations = Vec::new();
+            if let Some(params) = params {
+                let expected: Vec<String> = params.iter().map(|p| normalize(p)).collect();
+                if &expected != actual {
+                    violations.push(Violation {
+                        message: t!(
+                            "query.params",
+                            name,
+                            expected.join(", "),
+                            actual.join(", ")
+                        ),
+                        line: Some(*line),
+                    });
+                }
+            }
+            if let Some(returns) = returns {
+                if &normalize(returns) != ret {
+                    violations.push(Violation {
+                        message: t!(
+                            "query.returns",
+                            name,
+                            normalize(returns),
+                            ret
+                        ),
+                        line: Some(*line),
+                    });
+                }
+            }
+            violations
+        }
+        Query::NoGlobals => facts
+            .globals
+            .iter()
+            .map(|(name, line)| Violation {
+                message: t!("query.global", name),
+                line: Some(*line),
+            })
+            .collect(),
+    }
+}
+
+fn check_record(union: bool, name: &Option<String>, min_fields: usize, facts: &Facts) -> Vec<Violation> {
+    let candidates: Vec<_> = facts
+        .records
+        .iter()
+        .filter(|(is_union, record, _)| *is_union == union && (name.is_none() || record == name))
+        .collect();
+    // 结构体和联合体的消息分开写, 因为不同语言里名字和类型的顺序不一样
+    let message = match (candidates.is_empty(), name, union) {
+        (true, Some(name), false) => t!("query.no_struct_named", name),
+        (true, Some(name), true) => t!("query.no_union_named", name),
+        (true, None, false) => t!("query.no_struct").to_string(),
+        (true, None, true) => t!("query.no_union").to_string(),
+        (false, _, _) if candidates.iter().any(|(_, _, fields)| *fields >= min_fields) => {
+            return Vec::new()
+        }
+        (false, Some(name), false) => t!("query.struct_fields", name, min_fields),
+        (false, Some(name), true) => t!("query.union_fields", name, min_fields),
+        (false, None, false) => t!("query.any_struct_fields", min_fields),
+        (false, None, true) => t!("query.any_union_fields", min_fields),
+    };
+    vec![Violation { message, line: None }]
+}
+
+// 只收集学生代码中的定义, 忽略 #include 进来的头文件
+fn collect(parse: &Parse, path: &Path) -> Facts {
+    let location = |span: &Span| {
+        let (location, _) = loc::get_location_for_offset(&parse.source, span.start);
+        (Path::new(location.file) == path, location.line)
+    };
+
+    let mut facts = Facts::default();
+    let mut records = RecordCollector {
+        records: Vec::new(),
+        location: &location,
+    };
+    records.visit_translation_unit(&parse.unit);
+    facts.records = records.records;
+
+    for declaration in &parse.unit.0 {
+        let (in_source, line) = location(&declaration.span);
+        if !in_source {
+            continue;
+        }
+        match &declaration.node {
+            ExternalDeclaration::FunctionDefinition(function) => {
+                let function = &function.node;
+                let name = match declarator_name(&function.declarator.node) {
+                    Some(name) => name,
+                    None => continue,
+                };
+                let returns = type_name(&function.specifiers, &function.declarator.node.derived);
+                let params = function
+                    .declarator
+                    .node
+                    .derived
+                    .iter()
+                    .find_map(|derived| match &derived.node {
+                        DerivedDeclarator::Function(f) => Some(&f.node.parameters),
+                        _ => None,
+                    })
+                    .map(|parameters| {
+                        parameters
+                            .iter()
+                            .map(|p| {
+                                let derived = p
+                                    .node
+                                    .declarator
+                                    .as_ref()
+                                    .map(|d| d.node.derived.as_slice())
+                                    .unwrap_or(&[]);
+                                type_name(&p.node.specifiers, derived)
+                            })
+                            .filter(|p| p != "void")
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                facts.functions.push((name, returns, params, line));
+            }
+            ExternalDeclaration::Declaration(declaration) => {
+                let declaration = &declaration.node;
+                // typedef 不是变量, extern 只是声明了在别处定义的变量
+                let skipped = declaration.specifiers.iter().any(|s| {
+                    matches!(
+                        &s.node,
+                        DeclarationSpecifier::StorageClass(c)
+                            if c.node == StorageClassSpecifier::Typedef
+                                || c.node == StorageClassSpecifier::Extern
+                    )
+                });
+                if skipped {
+                    continue;
+                }
+                for init in &declaration.declarators {
+                    let declarator = &init.node.declarator.node;
+                    let is_function = declarator
+                        .derived
+                        .iter()
+                        .any(|d| matches!(d.node, DerivedDeclarator::Function(_)));
+                    if let (false, Some(name)) = (is_function, declarator_name(declarator)) {
+                        facts.globals.push((name, line));
+                    }
+                }
+            }
+            ExternalDeclaration::StaticAssert(_) => {}
+        }
+    }
+    facts
+}
+
+// 在整个语法树中找出结构体和联合体的定义, 包括写在函数里面的
+struct RecordCollector<'a, F: Fn(&Span) -> (bool, usize)> {
+    records: Vec<(bool, Option<String>, usize)>,
+    location: &'a F,
+}
+
+impl<'a, 'ast, F: Fn(&Span) -> (bool, usize)> Visit<'ast> for RecordCollector<'a, F> {
+    fn visit_struct_type(&mut self, struct_type: &'ast StructType, span: &'ast Span) {
+        if let (Some(declarations), (true, _)) = (&struct_type.declarations, (self.location)(span)) {
+            let fields = declarations
+                .iter()
+                .map(|d| match &d.node {
+                    StructDeclaration::Field(field) => field.node.declarators.len().max(1),
+                    StructDeclaration::StaticAssert(_) => 0,
+                })
+                .sum();
+            self.records.push((
+                struct_type.kind.node == StructKind::Union,
+                struct_type.identifier.as_ref().map(|i| i.node.name.clone()),
+                fields,
+            ));
+        }
+        visit::visit_struct_type(self, struct_type, span);
+    }
+}
+
+fn declarator_name(declarator: &Declarator) -> Option<String> {
+    match &declarator.kind.node {
+        DeclaratorKind::Identifier(identifier) => Some(identifier.node.name.clone()),
+        DeclaratorKind::Declarator(inner) => declarator_name(&inner.node),
+        DeclaratorKind::Abstract => None,
+    }
+}
+
+// 把声明的类型写成 "const char *" 的样子
+fn type_name(specifiers: &[Node<DeclarationSpecifier>], derived: &[Node<DerivedDeclarator>]) -> String {
+    let mut words: Vec<String> = specifiers
+        .iter()
+        .filter_map(|s| match &s.node {
+            DeclarationSpecifier::TypeSpecifier(t) => Some(type_specifier_name(&t.node)),
+            DeclarationSpecifier::TypeQualifier(q) if q.node == TypeQualifier::Const => {
+                Some("const".to_string())
+            }
+            _ => None,
+        })
+        .collect();
+    for d in derived {
+        match d.node {
+            DerivedDeclarator::Pointer(_) | DerivedDeclarator::Array(_) => words.push("*".to_string()),
+            _ => {}
+        }
+    }
+    normalize(&words.join(" "))
+}
+
+fn type_specifier_name(specifier: &TypeSpecifier) -> String {
+    match specifier {
+        TypeSpecifier::Void => "void".to_string(),
+        TypeSpecifier::Char => "char".to_string(),
+        TypeSpecifier::Short => "short".to_string(),
+        TypeSpecifier::Int => "int".to_string(),
+        TypeSpecifier::Long => "long".to_string(),
+        TypeSpecifier::Float => "float".to_string(),
+        TypeSpecifier::Double => "double".to_string(),
+        TypeSpecifier::Signed => "signed".to_string(),
+        TypeSpecifier::Unsigned => "unsigned".to_string(),
+        TypeSpecifier::Bool => "_Bool".to_string(),
+        TypeSpecifier::Struct(s) => {
+            let kind = match s.node.kind.node {
+                StructKind::Struct => "struct",
+                StructKind::Union => "union",
+            };
+            match &s.node.identifier {
+                Some(identifier) => format!("{} {}", kind, identifier.node.name),
+                None => kind.to_string(),
+            }
+        }
+        TypeSpecifier::Enum(e) => match &e.node.identifier {
+            Some(identifier) => format!("enum {}", identifier.node.name),
+            None => "enum".to_string(),
+        },
+        TypeSpecifier::TypedefName(identifier) => identifier.node.name.clone(),
+        _ => "?".to_string(),
+    }
+}
+
+// 统一空白和 * 的写法, "char*" 和 "char  *" 都会变成 "char *"
+fn normalize(type_name: &str) -> String {
+    type_name
+        .replace('*', " * ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试结构体、函数和全局变量的查询
+    #[test]
+    fn test_run() {
+        let queries: Vec<Query> = toml::from_str::<Queries>(
+            r#"
+            [[queries]]
+            kind = "struct"
+            name = "point"
+            min_fields = 3
+
+            [[queries]]
+            kind = "function"
+            name = "add"
+            params = ["int", "int"]
+            returns = "int"
+
+            [[queries]]
+            kind = "function"
+            name = "show"
+            params = ["const char*"]
+
+            [[queries]]
+            kind = "no_globals"
+            "#,
+        )
+        .unwrap()
+        .queries;
+
+        let violations = run(&queries, Path::new("exercise_test/Query.c"), &[]);
+        assert_eq!(violations.len(), 3);
+        assert!(violations[0].message.contains("point"));
+        assert!(violations[1].message.contains("(int, int)"));
+        assert_eq!(violations[2].line, Some(6));
+    }
+
+    // 测试头文件目录会传给预处理器, 找不到头文件时检查不通过
+    #[test]
+    fn test_run_with_include_dirs() {
+        let queries = vec![Query::Function {
+            name: "main".to_string(),
+            params: None,
+            returns: None,
+        }];
+        let path = Path::new("exercise_test/multi/main.c");
+        let include = vec![PathBuf::from("exercise_test/multi/include")];
+        assert!(run(&queries, path, &include).is_empty());
+        let violations = run(&queries, path, &[]);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].line, None);
+    }
+
+    #[derive(Deserialize)]
+    struct Queries {
+        queries: Vec<Query>,
+    }
+}
@@ -0,0 +1,243 @@
+use crate::exercise::Exercise;
+use console::style;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, Write};
+
+// 答案文件中的一行答案: "题号. 答案", 题号后面也可以用 、 或者 :
+const ANSWER_REGEX: &str = r"^\s*(\d+)\s*[.、:：]\s*(.*?)\s*$";
+
+// info.toml 中声明的一道题目
+#[derive(Deserialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Question {
+    // 单选题, answer 是正确选项的字母, 例如 "B"
+    Single {
+        question: String,
+        options: Vec<String>,
+        answer: String,
+    },
+    // 多选题, answer 是所有正确选项的字母, 例如 ["A", "C"]
+    Multiple {
+        question: String,
+        options: Vec<String>,
+        answer: Vec<String>,
+    },
+    // 填空题, answer 中的任何一个都算正确
+    Blank {
+        question: String,
+        answer: Vec<String>,
+    },
+}
+
+impl Question {
+    // 判断学生的答案是否正确, 选择题不区分大小写, 也不在意字母之间的空格和逗号
+    pub fn is_correct(&self, answer: &str) -> bool {
+        match self {
+            Question::Single { answer: expected, .. } => letters(answer) == letters(expected),
+            Question::Multiple { answer: expected, .. } => {
+                letters(answer) == letters(&expected.concat())
+            }
+            Question::Blank { answer: expected, .. } => {
+                expected.iter().any(|expected| expected.trim() == answer.trim())
+            }
+        }
+    }
+
+    // 输出题目和选项
+    pub fn print(&self, number: usize) {
+        let (kind, question, options) = match self {
+            Question::Single { question, options, .. } => (t!("quiz.single"), question, options.as_slice()),
+            Question::Multiple { question, options, .. } => (t!("quiz.multiple"), question, options.as_slice()),
+            Question::Blank { question, .. } => (t!("quiz.blank"), question, &[][..]),
+        };
+        println!("{} {}", style(format!("{}. ({})", number, kind)).blue().bold(), question.trim());
+        for (letter, option) in ('A'..='Z').zip(options) {
+            println!("   {}. {}", letter, option);
+        }
+    }
+}
+
+// 选择题答案中的字母, 转成大写并去掉重复
+fn letters(answer: &str) -> BTreeSet<char> {
+    answer
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+// 从答案文件中读出每一道题的答案, 下标是题号减一, 没有作答的题目是空字符串
+pub fn parse_answers(text: &str, count: usize) -> Vec<String> {
+    let re = Regex::new(ANSWER_REGEX).unwrap();
+    let mut answers = vec![String::new(); count];
+    for line in text.lines().filter(|line| !line.trim_start().starts_with('#')) {
+        if let Some(caps) = re.captures(line) {
+            let number: usize = caps[1].parse().unwrap_or(0);
+            if (1..=count).contains(&number) {
+                answers[number - 1] = caps[2].to_string();
+            }
+        }
+    }
+    answers
+}
+
+// 批改答案文件, 返回答错或者没有作答的题号
+pub fn grade(exercise: &Exercise) -> Result<Vec<usize>, String> {
+    let text = fs::read_to_string(&exercise.path)
+        .map_err(|e| t!("quiz.read_failed", exercise.path.display(), e))?;
+    let answers = parse_answers(&text, exercise.questions.len());
+    Ok(exercise
+        .questions
+        .iter()
+        .zip(&answers)
+        .enumerate()
+        .filter(|(_, (question, answer))| !question.is_correct(answer))
+        .map(|(index, _)| index + 1)
+        .collect())
+}
+
+// 在终端中逐题作答, 答案会写回答案文件, 这样 verify 和 watch 也能批改
+pub fn quiz(exercise: &Exercise) -> Result<(), ()> {
+    let text = fs::read_to_string(&exercise.path).unwrap_or_default();
+    let mut answers = parse_answers(&text, exercise.questions.len());
+
+    for (index, question) in exercise.questions.iter().enumerate() {
+        question.print(index + 1);
+        print!("{}", t!("quiz.prompt", answers[index]));
+        io::stdout().flush().unwrap();
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input).is_err() {
+            return Err(());
+        }
+        if !input.trim().is_empty() {
+            answers[index] = input.trim().to_string();
+        }
+        if question.is_correct(&answers[index]) {
+            println!("{}", style(t!("quiz.correct")).green());
+        } else {
+            println!("{}", style(t!("quiz.wrong")).red());
+        }
+        println!();
+    }
+
+    if let Err(e) = fs::write(&exercise.path, write_answers(&text, &answers)) {
+        println!("{}", t!("quiz.save_failed", exercise.path.display(), e));
+        return Err(());
+    }
+
+    let wrong = grade(exercise).map_err(|e| println!("{}", e))?;
+    if wrong.is_empty() {
+        success!("{}", t!("quiz.all_correct", exercise));
+        Ok(())
+    } else {
+        print_wrong(exercise, &wrong);
+        Err(())
+    }
+}
+
+// 把答案写回答案文件, 保留原来的注释, 没有出现过的题号追加在最后
+fn write_answers(text: &str, answers: &[String]) -> String {
+    let re = Regex::new(ANSWER_REGEX).unwrap();
+    let mut written = vec![false; answers.len()];
+    let mut lines: Vec<String> = text
+        .lines()
+        .map(|line| {
+            let number = re
+                .captures(line)
+                .filter(|_| !line.trim_start().starts_with('#'))
+                .and_then(|caps| caps[1].parse::<usize>().ok())
+                .filter(|number| (1..=answers.len()).contains(number));
+            match number {
+                Some(number) => {
+                    written[number - 1] = true;
+                    format!("{}. {}", number, answers[number - 1])
+                }
+                None => line.to_string(),
+            }
+        })
+        .collect();
+    for (index, answer) in answers.iter().enumerate() {
+        if !written[index] {
+            lines.push(format!("{}. {}", index + 1, answer));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+// 输出答错的题号, 不给出正确答案
+pub fn print_wrong(exercise: &Exercise, wrong: &[usize]) {
+    warn!("{}", t!("quiz.not_all_correct", exercise));
+    for number in wrong {
+        exercise.questions[number - 1].print(*number);
+    }
+    println!();
+    println!("{}", t!("quiz.how_to_fix", exercise.path.display(), exercise.name));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试答案文件的解析和三种题目的批改
+    #[test]
+    fn test_grade() {
+        let questions: Vec<Question> = toml::from_str::<Questions>(
+            r#"
+            [[questions]]
+            kind = "single"
+            question = "C 程序从哪个函数开始执行?"
+            options = ["start", "main", "init"]
+            answer = "B"
+
+            [[questions]]
+            kind = "multiple"
+            question = "哪些是 C 语言的关键字?"
+            options = ["int", "printf", "return"]
+            answer = ["A", "C"]
+
+            [[questions]]
+            kind = "blank"
+            question = "把源文件变成可执行程序的过程叫做 ____"
+            answer = ["翻译", "编译"]
+            "#,
+        )
+        .unwrap()
+        .questions;
+
+        let answers = parse_answers("# I AM NOT DONE\n1. b\n2、c, a\n3: 编译\n# 4. A\n", 3);
+        assert_eq!(answers, vec!["b", "c, a", "编译"]);
+        assert!(questions.iter().zip(&answers).all(|(q, a)| q.is_correct(a)));
+        assert!(!questions[1].is_correct("A"));
+
+        let written = write_answers("# I AM NOT DONE\n1. \n", &answers);
+        assert_eq!(written, "# I AM NOT DONE\n1. b\n2. c, a\n3. 编译\n");
+    }
+
+    // 测试课程中的答题卡写出了所有的题目和选项, 学生不需要去 info.toml 中看题目
+    #[test]
+    fn test_cards_show_questions() {
+        let list: crate::exercise::ExerciseList =
+            toml::from_str(&fs::read_to_string("info.toml").unwrap()).unwrap();
+        for exercise in list.exercises.iter().filter(|e| !e.questions.is_empty()) {
+            let card = fs::read_to_string(&exercise.path).unwrap();
+            for question in &exercise.questions {
+                let (question, options) = match question {
+                    Question::Single { question, options, .. }
+                    | Question::Multiple { question, options, .. } => (question, options.as_slice()),
+                    Question::Blank { question, .. } => (question, &[][..]),
+                };
+                assert!(card.contains(question.trim()), "{}", question);
+                assert!(options.iter().all(|option| card.contains(option.as_str())));
+            }
+            assert!(!card.contains("info.toml"));
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Questions {
+        questions: Vec<Question>,
+    }
+}
@@ -1,46 +1,171 @@
-use crate::exercise::Exercise;
+use crate::exercise::{Exercise, Input, Mode};
+use crate::harness;
+use crate::leak;
+use crate::quiz;
+use crate::sanitizer;
+use crate::ui;
 use indicatif::ProgressBar;
+use std::time::Instant;
 
-pub fn run(exercise: &Exercise) -> Result<(), ()> {
-    compile_and_run(exercise)?;
-    Ok(())
+pub fn run(exercise: &Exercise, input: Input) -> Result<(), ()> {
+    match exercise.mode {
+        Mode::Compile => compile_only(exercise),
+        Mode::CompileFail => compile_fail(exercise),
+        Mode::Quiz => quiz::quiz(exercise),
+        Mode::Run | Mode::Test => compile_and_run(exercise, input),
+    }
 }
 
+fn compile_only(exercise: &Exercise) -> Result<(), ()> {
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_message(&t!("verify.compiling", exercise));
+    progress_bar.enable_steady_tick(100);
+
+    let started = Instant::now();
+    let compilation_result = exercise.compile();
+    progress_bar.finish_and_clear();
+    ui::timing(t!("verify.compile_time"), started.elapsed());
+
+    match compilation_result {
+        Ok(compilation) => {
+            ui::compiler_warnings(&compilation.warnings);
+            success!("{}", t!("run.compiled", exercise));
+            Ok(())
+        }
+        Err(output) => {
+            warn!("{}", t!("run.compile_failed", exercise));
+            println!("{}", output.stderr);
+            Err(())
+        }
+    }
+}
 
-fn compile_and_run(exercise: &Exercise) -> Result<(), ()> {
+fn compile_fail(exercise: &Exercise) -> Result<(), ()> {
     let progress_bar = ProgressBar::new_spinner();
-    progress_bar.set_message(format!("Compiling {}...", exercise).as_str());
+    progress_bar.set_message(&t!("verify.compiling", exercise));
     progress_bar.enable_steady_tick(100);
 
+    let started = Instant::now();
     let compilation_result = exercise.compile();
+    progress_bar.finish_and_clear();
+    ui::timing(t!("verify.compile_time"), started.elapsed());
+
+    match compilation_result {
+        Ok(_) => {
+            warn!("{}", t!("run.unexpected_success", exercise));
+            Err(())
+        }
+        Err(output) => {
+            println!("{}", output.stderr);
+            if exercise.expects_error(&output.stderr) {
+                success!("{}", t!("run.expected_error", exercise));
+                Ok(())
+            } else {
+                warn!("{}", t!("run.wrong_error", exercise));
+                Err(())
+            }
+        }
+    }
+}
+
+fn compile_and_run(exercise: &Exercise, input: Input) -> Result<(), ()> {
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_message(&t!("verify.compiling", exercise));
+    progress_bar.enable_steady_tick(100);
+
+    let started = Instant::now();
+    let compilation_result = exercise.compile();
+    progress_bar.finish_and_clear();
+    ui::timing(t!("verify.compile_time"), started.elapsed());
     let compilation = match compilation_result {
         Ok(compilation) => compilation,
         Err(output) => {
-            progress_bar.finish_and_clear();
-            warn!(
-                "{} 编译失败, 错误信息如下:\n",
-                exercise
-            );
+            warn!("{}", t!("run.compile_failed", exercise));
             println!("{}", output.stderr);
             return Err(());
         }
     };
+    ui::compiler_warnings(&compilation.warnings);
 
-    progress_bar.set_message(format!("正在运行 {}...", exercise).as_str());
-    let result = compilation.run();
+    // --nocapture 或者连接到终端时不显示进度条, 以免盖住程序的输出和提示
+    let interactive = input == Input::Terminal;
+    let progress_bar = if ui::nocapture() || interactive {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new_spinner()
+    };
+    progress_bar.set_message(&t!("verify.running", exercise));
+    progress_bar.enable_steady_tick(100);
+    let started = Instant::now();
+    let result = compilation.run(input);
     progress_bar.finish_and_clear();
+    ui::timing(t!("verify.run_time"), started.elapsed());
+    // 程序的输出已经直接显示在终端上时, 运行结束后不再重复输出
+    let print_output = |output: &str| {
+        if !ui::nocapture() && !interactive {
+            println!("{}", output);
+        }
+    };
+
+    if exercise.sanitize {
+        let output = match &result {
+            Ok(output) | Err(output) => output,
+        };
+        let findings = sanitizer::parse(&output.stderr);
+        if !findings.is_empty() {
+            print_output(&output.stdout);
+            warn!("{}", t!("verify.memory_error", exercise));
+            sanitizer::print_findings(&findings);
+            return Err(());
+        }
+    }
+
+    if exercise.leak_check {
+        let output = match &result {
+            Ok(output) | Err(output) => output,
+        };
+        let report = leak::parse(&output.stderr, &exercise.source_files());
+        if !report.passed() {
+            print_output(&output.stdout);
+            warn!("{}", t!("verify.leak", exercise));
+            report.print();
+            return Err(());
+        }
+    }
 
     match result {
+        Ok(output) if exercise.mode == Mode::Test => {
+            let report = harness::parse(&output.stdout);
+            print_output(&report.output);
+            report.print_assertions();
+            if report.passed() {
+                success!("{}", t!("run.test_passed", exercise));
+                Ok(())
+            } else {
+                warn!("{}", t!("run.test_failed", exercise));
+                Err(())
+            }
+        },
         Ok(output) => {
-            println!("{}", output.stdout);
-            success!("成功运行 {}", exercise);
+            print_output(&output.stdout);
+            success!("{}", t!("run.ran", exercise));
             Ok(())
         },
+        // 隐藏测试中途崩溃时, 先输出已经运行过的断言, 再报告崩溃
+        Err(output) if exercise.mode == Mode::Test => {
+            let report = harness::parse(&output.stdout);
+            print_output(&report.output);
+            print_output(&output.stderr);
+            report.print_assertions();
+
+            warn!("{}", t!("verify.run_failed", exercise));
+            Err(())
+        }
         Err(output) => {
-            println!("{}", output.stdout);
-            println!("{}", output.stderr);
+            print_output(&output.stdout);
+            print_output(&output.stderr);
 
-            warn!("{} 有如下错误", exercise);
+            warn!("{}", t!("verify.run_failed", exercise));
             Err(())
         }
     }
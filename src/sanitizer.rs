@@ -0,0 +1,140 @@
+use console::style;
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+
+// 开启 sanitizer 模式时额外传给 gcc 的参数
+pub const FLAGS: [&str; 3] = ["-g", "-fsanitize=address,undefined", "-fno-omit-frame-pointer"];
+
+// UBSan 报告的格式: 文件:行:列: runtime error: 描述
+const UBSAN_REGEX: &str = r"(?m)^(\S+?):(\d+):\d+: runtime error: (.*)$";
+// ASan 报告最后的总结: SUMMARY: AddressSanitizer: 错误类型 文件:行[:列] in 函数
+const ASAN_REGEX: &str = r"(?m)^SUMMARY: AddressSanitizer: ([\w-]+) (\S+?):(\d+)(?::\d+)? in ";
+
+// sanitizer 发现的一个问题
+#[derive(PartialEq, Debug)]
+pub struct Finding {
+    // 出问题的源文件
+    pub file: String,
+    // 出问题的行号 (从 1 开始)
+    pub line: usize,
+    // sanitizer 给出的原始描述
+    pub message: String,
+    // 给初学者看的解释
+    pub explanation: &'static str,
+}
+
+// 从程序的 stderr 中解析出 AddressSanitizer 和 UndefinedBehaviorSanitizer 的报告
+pub fn parse(stderr: &str) -> Vec<Finding> {
+    let ubsan = Regex::new(UBSAN_REGEX).unwrap();
+    let asan = Regex::new(ASAN_REGEX).unwrap();
+
+    let mut findings: Vec<Finding> = ubsan
+        .captures_iter(stderr)
+        .map(|caps| Finding {
+            file: caps[1].to_string(),
+            line: caps[2].parse().unwrap_or(0),
+            message: caps[3].to_string(),
+            explanation: explain_undefined(&caps[3]),
+        })
+        .collect();
+
+    findings.extend(asan.captures_iter(stderr).map(|caps| Finding {
+        file: caps[2].to_string(),
+        line: caps[3].parse().unwrap_or(0),
+        message: caps[1].to_string(),
+        explanation: explain_address(&caps[1]),
+    }));
+
+    // 同一行往往会被两种 sanitizer 重复报告, 只保留第一条
+    let mut seen = HashSet::new();
+    findings.retain(|f| seen.insert((f.file.clone(), f.line)));
+    findings
+}
+
+// 输出每一个问题, 以及出问题的那一行代码
+pub fn print_findings(findings: &[Finding]) {
+    for finding in findings {
+        println!(
+            "{}:{} {}",
+            style(&finding.file).blue().bold(),
+            style(finding.line).blue().bold(),
+            style(finding.explanation).red()
+        );
+        if let Some(source) = source_line(&finding.file, finding.line) {
+            println!(
+                "{:>4} {}  {}",
+                style(finding.line).blue().bold(),
+                style("|").blue(),
+                source.trim_end()
+            );
+        }
+        println!("     ({})", finding.message);
+        println!();
+    }
+}
+
+// 读出源文件的第 line 行
+fn source_line(file: &str, line: usize) -> Option<String> {
+    let source = fs::read_to_string(file).ok()?;
+    source.lines().nth(line.checked_sub(1)?).map(str::to_string)
+}
+
+fn explain_address(kind: &str) -> &'static str {
+    match kind {
+        "stack-buffer-overflow" => t!("sanitizer.stack_buffer_overflow"),
+        "heap-buffer-overflow" => t!("sanitizer.heap_buffer_overflow"),
+        "global-buffer-overflow" => t!("sanitizer.global_buffer_overflow"),
+        "heap-use-after-free" => t!("sanitizer.use_after_free"),
+        "double-free" => t!("sanitizer.double_free"),
+        "stack-use-after-return" | "stack-use-after-scope" => t!("sanitizer.use_after_scope"),
+        "SEGV" => t!("sanitizer.segv"),
+        "bad-free" => t!("sanitizer.bad_free"),
+        _ => t!("sanitizer.bad_access"),
+    }
+}
+
+fn explain_undefined(message: &str) -> &'static str {
+    if message.contains("out of bounds") || message.contains("insufficient space") {
+        t!("sanitizer.out_of_bounds")
+    } else if message.contains("signed integer overflow") {
+        t!("sanitizer.signed_overflow")
+    } else if message.contains("division by zero") {
+        t!("sanitizer.division_by_zero")
+    } else if message.contains("null pointer") {
+        t!("sanitizer.null_pointer")
+    } else if message.contains("shift") {
+        t!("sanitizer.bad_shift")
+    } else if message.contains("misaligned") {
+        t!("sanitizer.misaligned")
+    } else {
+        t!("sanitizer.undefined")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试能从 ASan 和 UBSan 的报告中解析出问题所在的行
+    #[test]
+    fn test_parse() {
+        let stderr = "\
+n.c:2:31: runtime error: signed integer overflow: 2147483647 + 1 cannot be represented in type 'int'
+n.c:2:31: runtime error: load of address 0x7ffe20ee14ac with insufficient space for an object of type 'int'
+=================================================================
+==2062==ERROR: AddressSanitizer: stack-buffer-overflow on address 0x7fff8f93d974
+WRITE of size 4 at 0x7fff8f93d974 thread T0
+    #0 0x5644b03fc35a in main /tmp/s.c:3
+SUMMARY: AddressSanitizer: stack-buffer-overflow /tmp/s.c:3 in main
+";
+        let findings = parse(stderr);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].file, "n.c");
+        assert_eq!(findings[0].line, 2);
+        assert_eq!(findings[0].explanation, explain_undefined("signed integer overflow"));
+        assert_eq!(findings[1].file, "/tmp/s.c");
+        assert_eq!(findings[1].line, 3);
+        assert_eq!(findings[1].message, "stack-buffer-overflow");
+    }
+}
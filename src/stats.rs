@@ -0,0 +1,157 @@
+use crate::exercise::Exercise;
+use console::{pad_str, style, Alignment};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+// clearning 保存学习记录的文件夹
+pub const DATA_DIR: &str = ".clearning";
+// 每个练习花费的时间 (秒), 只在 watch 模式下记录
+const TIME_FILE: &str = ".clearning/time.json";
+
+// 读取每个练习已经花费的时间, 文件不存在或者损坏时当作没有记录
+pub fn load() -> BTreeMap<String, u64> {
+    fs::read_to_string(TIME_FILE)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+// 给一个练习加上花费的时间
+pub fn record(id: &str, seconds: u64) {
+    let mut times = load();
+    *times.entry(id.to_string()).or_insert(0) += seconds;
+    save(&times);
+}
+
+fn save(times: &BTreeMap<String, u64>) {
+    let saved = fs::create_dir_all(DATA_DIR).and_then(|_| {
+        fs::write(
+            Path::new(TIME_FILE),
+            serde_json::to_string_pretty(times).unwrap(),
+        )
+    });
+    if let Err(e) = saved {
+        println!("{}", t!("stats.save_failed", TIME_FILE, e));
+    }
+}
+
+// 练习有 id 之前的学习记录是按照名字保存的, 把它们改成按照 id 保存
+pub fn migrate(exercises: &[Exercise]) {
+    let mut times = load();
+    if migrate_keys(&mut times, exercises) {
+        save(&times);
+    }
+}
+
+fn migrate_keys(times: &mut BTreeMap<String, u64>, exercises: &[Exercise]) -> bool {
+    let mut changed = false;
+    for exercise in exercises {
+        if exercise.id() == exercise.name {
+            continue;
+        }
+        if let Some(seconds) = times.remove(&exercise.name) {
+            *times.entry(exercise.id().to_string()).or_insert(0) += seconds;
+            changed = true;
+        }
+    }
+    changed
+}
+
+// 花费的时间和预计时间的合计 (分钟), 没有预计时间的练习不计入预计时间
+fn totals(exercises: &[Exercise], times: &BTreeMap<String, u64>) -> (u64, u64) {
+    let spent = exercises
+        .iter()
+        .filter_map(|e| times.get(e.id()))
+        .sum::<u64>()
+        / 60;
+    let estimated = exercises
+        .iter()
+        .filter_map(|e| e.estimated_minutes)
+        .map(u64::from)
+        .sum();
+    (spent, estimated)
+}
+
+// 输出每个练习花费的时间和预计的时间
+pub fn print_summary(exercises: &[Exercise]) {
+    let times = load();
+    if times.is_empty() {
+        return;
+    }
+    println!("{}", style(t!("stats.title")).bold());
+    for exercise in exercises {
+        let spent = times.get(exercise.id()).map(|seconds| seconds / 60);
+        let spent = match spent {
+            Some(minutes) => t!("stats.minutes", minutes),
+            None => "-".to_string(),
+        };
+        let estimated = match exercise.estimated_minutes {
+            Some(minutes) => t!("stats.estimated", minutes),
+            None => String::new(),
+        };
+        println!(
+            "  {} {:>8}  {}",
+            pad_str(&exercise.name, 24, Alignment::Left, None),
+            spent,
+            style(estimated).dim()
+        );
+    }
+    let (spent, estimated) = totals(exercises, &times);
+    println!(
+        "{}",
+        t!("stats.total", style(spent).bold(), style(estimated).bold())
+    );
+    println!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试花费时间和预计时间的合计
+    #[test]
+    fn test_totals() {
+        let exercises = vec![
+            Exercise {
+                name: "int01".to_string(),
+                estimated_minutes: Some(10),
+                ..Default::default()
+            },
+            Exercise {
+                name: "char类型".to_string(),
+                ..Default::default()
+            },
+        ];
+        let times: BTreeMap<String, u64> = vec![
+            ("int01".to_string(), 300),
+            ("char类型".to_string(), 330),
+            ("其他练习".to_string(), 6000),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(totals(&exercises, &times), (10, 10));
+    }
+
+    // 测试按照名字保存的学习记录会改成按照 id 保存
+    #[test]
+    fn test_migrate_keys() {
+        let exercises = vec![Exercise {
+            name: "main函数".to_string(),
+            id: Some("main-function".to_string()),
+            ..Default::default()
+        }];
+        let mut times: BTreeMap<String, u64> = vec![
+            ("main函数".to_string(), 60),
+            ("main-function".to_string(), 30),
+            ("其他练习".to_string(), 10),
+        ]
+        .into_iter()
+        .collect();
+        assert!(migrate_keys(&mut times, &exercises));
+        assert_eq!(times.get("main-function"), Some(&90));
+        assert_eq!(times.get("main函数"), None);
+        assert_eq!(times.get("其他练习"), Some(&10));
+        assert!(!migrate_keys(&mut times, &exercises));
+    }
+}
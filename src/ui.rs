@@ -1,10 +1,16 @@
+use console::style;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
 macro_rules! warn {
     ($fmt:literal, $ex:expr) => {{
-        use console::{style, Emoji};
+        use console::style;
         let formatstr = format!($fmt, $ex);
         println!(
             "{} {}",
-            style(Emoji("⚠️ ", "!")).red(),
+            style($crate::ui::emoji("⚠️ ", "!")).red(),
             style(formatstr).red()
         );
     }};
@@ -12,12 +18,73 @@ macro_rules! warn {
 
 macro_rules! success {
     ($fmt:literal, $ex:expr) => {{
-        use console::{style, Emoji};
+        use console::style;
         let formatstr = format!($fmt, $ex);
         println!(
             "{} {}",
-            style(Emoji("✅", "✓")).green(),
+            style($crate::ui::emoji("✅", "✓")).green(),
             style(formatstr).green()
         );
     }};
-}
\ No newline at end of file
+}
+
+// 在配置中关掉 emoji 后, 即使终端支持也只使用 fallback
+pub fn emoji<'a>(emoji: &'a str, fallback: &'a str) -> console::Emoji<'a, 'a> {
+    if crate::config::get().emoji() {
+        console::Emoji(emoji, fallback)
+    } else {
+        console::Emoji(fallback, fallback)
+    }
+}
+
+// 输出的详细程度: -q 只输出结果, -v 还会输出编译命令和用时
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+static VERBOSITY: OnceLock<Verbosity> = OnceLock::new();
+static NOCAPTURE: AtomicBool = AtomicBool::new(false);
+
+// 根据命令行参数设置输出方式, 只在程序开始时调用一次
+pub fn init(verbosity: Verbosity, nocapture: bool) {
+    let _ = VERBOSITY.set(verbosity);
+    NOCAPTURE.store(nocapture, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    *VERBOSITY.get().unwrap_or(&Verbosity::Normal)
+}
+
+// --nocapture: 练习程序的输出在运行时直接显示出来
+pub fn nocapture() -> bool {
+    NOCAPTURE.load(Ordering::Relaxed)
+}
+
+// -v 时输出将要运行的命令
+pub fn command(command: &Command) {
+    if verbosity() == Verbosity::Verbose {
+        println!("{} {:?}", style("$").dim(), style(command).dim());
+    }
+}
+
+// -v 时才输出的信息
+pub fn verbose(message: &str) {
+    if verbosity() == Verbosity::Verbose {
+        println!("{}", style(message).dim());
+    }
+}
+
+// -v 时输出编译或者运行花费的时间
+pub fn timing(what: &str, elapsed: Duration) {
+    verbose(&format!("{}: {} ms", what, elapsed.as_millis()));
+}
+
+// 通过编译时 gcc 给出的警告, -q 时不输出
+pub fn compiler_warnings(warnings: &str) {
+    if verbosity() >= Verbosity::Normal && !warnings.trim().is_empty() {
+        println!("{}", style(warnings.trim_end()).yellow());
+    }
+}
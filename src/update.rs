@@ -0,0 +1,221 @@
+use crate::course::{self, Course};
+use crate::exercise::ExerciseList;
+use console::style;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// 创建或者更新练习时课程文件的哈希, 用来判断学生修改过哪些文件
+const BASE: &str = ".clearning/base.json";
+
+#[derive(Serialize, Deserialize, Default)]
+struct Base {
+    // 练习文件夹当前的课程版本
+    version: Option<u32>,
+    // 文件相对于课程根目录的路径和当时内容的 sha256
+    files: BTreeMap<PathBuf, String>,
+}
+
+// 课程中的一个文件应该怎样合并到练习文件夹中
+#[derive(PartialEq, Debug)]
+enum Merge {
+    // 两边的内容一样
+    Unchanged,
+    // 练习文件夹中还没有这个文件
+    Add,
+    // 学生没有修改过这个文件, 直接使用新版本
+    Update,
+    // 只有学生修改过这个文件, 保留学生的版本
+    Keep,
+    // 学生和新版本的课程都修改过这个文件
+    Conflict,
+}
+
+fn hash(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+// base 是创建练习时这个文件的哈希, current 是练习文件夹中现在的内容
+fn merge(base: Option<&str>, current: Option<&[u8]>, new: &[u8]) -> Merge {
+    let current = match current {
+        Some(current) => current,
+        None => return Merge::Add,
+    };
+    if current == new {
+        return Merge::Unchanged;
+    }
+    match base {
+        Some(base) if base == hash(current) => Merge::Update,
+        Some(base) if base == hash(new) => Merge::Keep,
+        // 没有记录时无法知道是谁修改的, 当作冲突交给学生处理
+        _ => Merge::Conflict,
+    }
+}
+
+fn parse_manifest(contents: &[u8]) -> Result<ExerciseList, String> {
+    toml::from_str(&String::from_utf8_lossy(contents))
+        .map_err(|e| t!("update.bad_manifest", course::MANIFEST, e))
+}
+
+// 记录 dir 中的练习是由哪些课程文件创建的
+pub fn save_base(dir: &Path, files: &BTreeMap<PathBuf, Vec<u8>>) -> io::Result<()> {
+    let version = files
+        .get(Path::new(course::MANIFEST))
+        .and_then(|manifest| parse_manifest(manifest).ok())
+        .and_then(|list| list.version);
+    let base = Base {
+        version,
+        files: files
+            .iter()
+            .map(|(path, contents)| (path.clone(), hash(contents)))
+            .collect(),
+    };
+    let path = dir.join(BASE);
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(path, serde_json::to_string_pretty(&base).unwrap())
+}
+
+// 没有记录时 (例如 update 出现之前创建的练习) 所有不同的文件都当作冲突
+fn load_base() -> Base {
+    fs::read_to_string(BASE)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn write(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
+}
+
+// 冲突时新版本的文件写在旁边, 例如 int01.c.new
+fn new_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.new", path.display()))
+}
+
+fn version_name(version: Option<u32>) -> String {
+    version.map_or_else(|| t!("update.no_version").to_string(), |v| v.to_string())
+}
+
+// update: 把新版本的课程合并到当前的练习文件夹中
+pub fn update(course: &Course) -> Result<(), ()> {
+    let old = fs::read(course::MANIFEST).map_err(|_| {
+        warn!("{}", t!("update.not_course", course::MANIFEST))
+    })?;
+    let old = parse_manifest(&old).map_err(|e| warn!("{}", e))?;
+    let files = course::files(course).map_err(|e| warn!("{}", t!("course.read_failed", e)))?;
+    let manifest = files
+        .get(Path::new(course::MANIFEST))
+        .ok_or_else(|| warn!("{}", t!("course.no_manifest", course::MANIFEST)))?;
+    let new = parse_manifest(manifest).map_err(|e| warn!("{}", e))?;
+
+    if let (Some(old_version), Some(new_version)) = (old.version, new.version) {
+        if new_version <= old_version {
+            success!("{}", t!("update.up_to_date", old_version));
+            return Ok(());
+        }
+    }
+
+    let mut base = load_base();
+    let failed = |e: io::Error| warn!("{}", t!("update.failed", e));
+
+    // 练习改了路径时, 把学生的代码移动到新的位置, 再和新版本比较
+    let mut moved = Vec::new();
+    for exercise in &new.exercises {
+        let previous = old.exercises.iter().find(|e| e.id() == exercise.id());
+        let previous = match previous {
+            Some(previous) if previous.path != exercise.path => previous,
+            _ => continue,
+        };
+        if !previous.path.exists() || exercise.path.exists() {
+            continue;
+        }
+        if let Some(parent) = exercise.path.parent() {
+            fs::create_dir_all(parent).map_err(failed)?;
+        }
+        fs::rename(&previous.path, &exercise.path).map_err(failed)?;
+        if let Some(hash) = base.files.remove(&previous.path) {
+            base.files.insert(exercise.path.clone(), hash);
+        }
+        moved.push((&previous.path, &exercise.path));
+    }
+
+    let mut updated = 0;
+    let mut kept = 0;
+    let mut conflicts = Vec::new();
+    for (path, contents) in &files {
+        // info.toml 属于课程, 最后直接替换
+        if path == Path::new(course::MANIFEST) {
+            continue;
+        }
+        let current = fs::read(path).ok();
+        let base_hash = base.files.get(path).map(String::as_str);
+        match merge(base_hash, current.as_deref(), contents) {
+            Merge::Unchanged => {}
+            Merge::Add | Merge::Update => {
+                write(path, contents).map_err(failed)?;
+                updated += 1;
+            }
+            Merge::Keep => kept += 1,
+            Merge::Conflict => {
+                write(&new_path(path), contents).map_err(failed)?;
+                conflicts.push(path);
+            }
+        }
+    }
+    write(Path::new(course::MANIFEST), manifest).map_err(failed)?;
+    save_base(Path::new("."), &files).map_err(failed)?;
+
+    success!("{}", t!("update.updated", version_name(new.version)));
+    for exercise in &new.exercises {
+        if old.exercises.iter().all(|e| e.id() != exercise.id()) {
+            println!("  {} {}", style(t!("update.added")).green(), exercise.name);
+        }
+    }
+    for (from, to) in moved {
+        println!("  {} {} -> {}", style(t!("update.moved")).cyan(), from.display(), to.display());
+    }
+    for exercise in &old.exercises {
+        if new.exercises.iter().all(|e| e.id() != exercise.id()) {
+            println!(
+                "  {} {}",
+                style(t!("update.removed")).yellow(),
+                t!("update.removed_kept", exercise.name, exercise.path.display())
+            );
+        }
+    }
+    println!("{}", t!("update.summary", updated, kept));
+
+    if !conflicts.is_empty() {
+        println!();
+        warn!("{}", t!("update.conflicts", conflicts.len()));
+        for path in conflicts {
+            println!("    {} <- {}", path.display(), new_path(path).display());
+        }
+        println!("{}", t!("update.resolve"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试根据创建练习时的哈希判断文件的合并方式
+    #[test]
+    fn test_merge() {
+        let base = hash(b"old");
+        let base = Some(base.as_str());
+        assert_eq!(merge(base, None, b"new"), Merge::Add);
+        assert_eq!(merge(base, Some(b"new"), b"new"), Merge::Unchanged);
+        assert_eq!(merge(base, Some(b"old"), b"new"), Merge::Update);
+        assert_eq!(merge(base, Some(b"mine"), b"old"), Merge::Keep);
+        assert_eq!(merge(base, Some(b"mine"), b"new"), Merge::Conflict);
+        assert_eq!(merge(None, Some(b"mine"), b"new"), Merge::Conflict);
+    }
+}
@@ -1,66 +1,231 @@
-use crate::exercise::{CompiledExercise, Exercise, State};
+use crate::cache;
+use crate::check;
+use crate::config;
+use crate::exercise::{CompiledExercise, Exercise, Input, Mode, State};
+use crate::harness;
+use crate::leak;
+use crate::quiz;
+use crate::sanitizer;
+use crate::ui;
 use console::style;
 use indicatif::ProgressBar;
+use std::time::Instant;
 
 pub fn verify<'a>(
     start_at: impl IntoIterator<Item = &'a Exercise>,
 ) -> Result<(), &'a Exercise> {
     for exercise in start_at {
-        let compile_result = compile_and_run_interactively(&exercise);
+        // 已经完成并且通过过检验的练习, 只要没有修改就不再重新检验
+        let key = exercise.cache_key();
+        if exercise.state() == State::Done && cache::passed(&key) {
+            success!("{}", t!("verify.cached", exercise));
+            continue;
+        }
+        let compile_result = match exercise.mode {
+            Mode::Compile => compile_only_interactively(exercise),
+            Mode::CompileFail => compile_fail_interactively(exercise),
+            Mode::Quiz => grade_quiz(exercise),
+            Mode::Run | Mode::Test => compile_and_run_interactively(exercise),
+        };
         if !compile_result.unwrap_or(false) {
-            println!("练习文件的路径: {:?}\n", exercise.path);
+            println!("{}", t!("verify.path", format!("{:?}", exercise.path)));
             return Err(exercise);
         }
+        if exercise.state() == State::Done {
+            cache::mark_passed(&key);
+        }
     }
     Ok(())
 }
 
+// 只需要通过编译和代码检查的练习
+fn compile_only_interactively(exercise: &Exercise) -> Result<bool, ()> {
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_message(&t!("verify.compiling", exercise));
+    progress_bar.enable_steady_tick(100);
+
+    compile(exercise, &progress_bar)?;
+
+    check_source(exercise)?;
+
+    success!("{}", t!("verify.compiled", exercise));
+
+    Ok(prompt_for_completion(exercise, None))
+}
+
+// 需要产生预期编译错误的练习
+fn compile_fail_interactively(exercise: &Exercise) -> Result<bool, ()> {
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_message(&t!("verify.compiling", exercise));
+    progress_bar.enable_steady_tick(100);
+
+    let started = Instant::now();
+    let compilation_result = exercise.compile();
+    progress_bar.finish_and_clear();
+    ui::timing(t!("verify.compile_time"), started.elapsed());
+
+    match compilation_result {
+        Ok(_) => {
+            warn!("{}", t!("verify.unexpected_success", exercise));
+            print_expected_error(exercise);
+            Err(())
+        }
+        Err(output) if exercise.expects_error(&output.stderr) => {
+            success!("{}", t!("verify.expected_error", exercise));
+            Ok(prompt_for_completion(exercise, Some(output.stderr)))
+        }
+        Err(output) => {
+            warn!("{}", t!("verify.wrong_error", exercise));
+            println!("{}", output.stderr);
+            print_expected_error(exercise);
+            Err(())
+        }
+    }
+}
+
+// 批改答案文件中的题目
+fn grade_quiz(exercise: &Exercise) -> Result<bool, ()> {
+    let wrong = quiz::grade(exercise).map_err(|e| warn!("{}", e))?;
+    if !wrong.is_empty() {
+        quiz::print_wrong(exercise, &wrong);
+        return Err(());
+    }
+
+    success!("{}", t!("verify.quiz_passed", exercise));
+
+    Ok(prompt_for_completion(exercise, None))
+}
+
+fn print_expected_error(exercise: &Exercise) {
+    if let Some(expected) = &exercise.expected_error {
+        let expected = expected.strip_prefix(check::REGEX_PREFIX).unwrap_or(expected);
+        println!("{}", t!("verify.expected_error_is", style(expected).bold()));
+    }
+}
+
 fn compile_and_run_interactively(exercise: &Exercise) -> Result<bool, ()> {
     let progress_bar = ProgressBar::new_spinner();
-    progress_bar.set_message(format!("正在编译 {}...", exercise).as_str());
+    progress_bar.set_message(&t!("verify.compiling", exercise));
     progress_bar.enable_steady_tick(100);
 
-    let compilation = compile(&exercise, &progress_bar)?;
+    let compilation = compile(exercise, &progress_bar)?;
 
-    progress_bar.set_message(format!("正在运行 {}...", exercise).as_str());
-    let result = compilation.run();
+    // --nocapture 时程序的输出直接显示在终端上, 不显示进度条, 以免盖住程序的输出
+    let progress_bar = if ui::nocapture() {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new_spinner()
+    };
+    progress_bar.set_message(&t!("verify.running", exercise));
+    progress_bar.enable_steady_tick(100);
+    let started = Instant::now();
+    let result = compilation.run(Input::Null);
     progress_bar.finish_and_clear();
+    ui::timing(t!("verify.run_time"), started.elapsed());
+
+    // 即使程序正常退出, sanitizer 报告了问题也算失败
+    if exercise.sanitize {
+        let output = match &result {
+            Ok(output) | Err(output) => output,
+        };
+        let findings = sanitizer::parse(&output.stderr);
+        if !findings.is_empty() {
+            warn!("{}", t!("verify.memory_error", exercise));
+            sanitizer::print_findings(&findings);
+            return Err(());
+        }
+    }
+
+    if exercise.leak_check {
+        let output = match &result {
+            Ok(output) | Err(output) => output,
+        };
+        let report = leak::parse(&output.stderr, &exercise.source_files());
+        if !report.passed() {
+            warn!("{}", t!("verify.leak", exercise));
+            report.print();
+            return Err(());
+        }
+    }
 
     let output = match result {
         Ok(output) => output,
+        // 隐藏测试中途崩溃时, 先输出已经运行过的断言, 再报告崩溃
+        Err(output) if exercise.mode == Mode::Test => {
+            let report = harness::parse(&output.stdout);
+            report.print_assertions();
+            warn!("{}", t!("verify.run_failed", exercise));
+            if !ui::nocapture() {
+                println!("{}", report.output);
+                println!("{}", output.stderr);
+            }
+            return Err(());
+        }
         Err(output) => {
-            warn!("{} 有如下错误", exercise);
-            println!("{}", output.stdout);
-            println!("{}", output.stderr);
+            warn!("{}", t!("verify.run_failed", exercise));
+            if !ui::nocapture() {
+                println!("{}", output.stdout);
+                println!("{}", output.stderr);
+            }
             return Err(());
         }
     };
 
-    success!("{} 成功运行!", exercise);
+    check_source(exercise)?;
 
-    Ok(prompt_for_completion(&exercise, Some(output.stdout)))
+    if exercise.mode == Mode::Test {
+        let report = harness::parse(&output.stdout);
+        report.print_assertions();
+        if !report.passed() {
+            warn!("{}", t!("verify.test_failed", exercise));
+            return Err(());
+        }
+        success!("{}", t!("verify.test_passed", exercise));
+        return Ok(prompt_for_completion(exercise, Some(report.output)));
+    }
+
+    success!("{}", t!("verify.ran", exercise));
+
+    Ok(prompt_for_completion(exercise, Some(output.stdout)))
 }
 
-fn compile<'a, 'b>(
+fn compile<'a>(
     exercise: &'a Exercise,
-    progress_bar: &'b ProgressBar,
+    progress_bar: &ProgressBar,
 ) -> Result<CompiledExercise<'a>, ()> {
+    let started = Instant::now();
     let compilation_result = exercise.compile();
+    progress_bar.finish_and_clear();
+    ui::timing(t!("verify.compile_time"), started.elapsed());
 
     match compilation_result {
-        Ok(compilation) => Ok(compilation),
+        Ok(compilation) => {
+            ui::compiler_warnings(&compilation.warnings);
+            Ok(compilation)
+        }
         Err(output) => {
-            progress_bar.finish_and_clear();
-            warn!(
-                "{} 编译失败! 再试一次. 这一次的输出如下:",
-                exercise
-            );
+            warn!("{}", t!("verify.compile_failed", exercise));
             println!("{}", output.stderr);
             Err(())
         }
     }
 }
 
+// 按照 info.toml 中的规则和结构化查询检查代码
+fn check_source(exercise: &Exercise) -> Result<(), ()> {
+    if exercise.checks.is_empty() && exercise.queries.is_empty() {
+        return Ok(());
+    }
+    let violations = exercise.check_source();
+    if !violations.is_empty() {
+        warn!("{}", t!("verify.check_failed", exercise));
+        check::print_violations(&violations);
+        println!();
+        return Err(());
+    }
+    Ok(())
+}
+
 fn prompt_for_completion(exercise: &Exercise, prompt_output: Option<String>) -> bool {
     let context = match exercise.state() {
         State::Done => return true,
@@ -68,21 +233,30 @@ fn prompt_for_completion(exercise: &Exercise, prompt_output: Option<String>) ->
     };
 
     println!();
-    println!("🎉 🎉  通过编译了! 🎉 🎉");
+    match exercise.mode {
+        Mode::CompileFail => println!("{}", t!("verify.celebrate_error")),
+        Mode::Quiz => println!("{}", t!("verify.celebrate_quiz")),
+        _ => println!("{}", t!("verify.celebrate")),
+    }
     println!();
 
     if let Some(output) = prompt_output {
-        println!("输出:");
+        println!("{}", t!("verify.output"));
         println!("{}", separator());
         println!("{}", output);
         println!("{}", separator());
         println!();
     }
 
-    println!("你可以继续改一下这一次练习的代码,");
+    if config::get().auto_advance() && exercise.remove_marker().is_ok() {
+        println!("{}", t!("verify.advanced"));
+        return true;
+    }
+
+    println!("{}", t!("verify.keep_working"));
     println!(
-        "或者去掉 {} 这个注释，然后做下一个练习:",
-        style("`I AM NOT DONE`").bold()
+        "{}",
+        t!("verify.remove_marker", style("`I AM NOT DONE`").bold())
     );
     println!();
     for context_line in context {
@@ -0,0 +1,251 @@
+use crate::config;
+use crate::doc;
+use crate::exam;
+use crate::exercise::Exercise;
+use crate::stats;
+use crate::verify::verify;
+use notify::DebouncedEvent;
+use notify::{PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::any::Any;
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 收到第一个事件后, 再等待这么久把同一次保存产生的事件合并起来
+const COALESCE: Duration = Duration::from_millis(300);
+
+// 开始监视 root 文件夹
+// 优先使用系统的文件通知 (inotify 等), 失败时退回到轮询
+// 返回的 watcher 只需要被持有, 它被 drop 后监视就会停止
+// notify 自带的防抖间隔可以在配置中修改 (debounce)
+pub fn start_watcher(root: &Path) -> notify::Result<(Box<dyn Any>, Receiver<DebouncedEvent>)> {
+    let (tx, rx) = channel();
+    let native = RecommendedWatcher::new(tx, config::get().debounce()).and_then(|mut watcher| {
+        watcher.watch(root, RecursiveMode::Recursive)?;
+        Ok(watcher)
+    });
+    if let Ok(watcher) = native {
+        return Ok((Box::new(watcher), rx));
+    }
+
+    println!("{}", t!("watch.polling"));
+    let (tx, rx) = channel();
+    let mut watcher = PollWatcher::new(tx, config::get().debounce())?;
+    watcher.watch(root, RecursiveMode::Recursive)?;
+    Ok((Box::new(watcher), rx))
+}
+
+fn spawn_watch_shell(
+    failed_exercise_hint: &Arc<Mutex<Option<String>>>,
+    failed_exercise_notes: &Arc<Mutex<Option<PathBuf>>>,
+) {
+    let failed_exercise_hint = Arc::clone(failed_exercise_hint);
+    let failed_exercise_notes = Arc::clone(failed_exercise_notes);
+    println!("{}", t!("watch.help"));
+    thread::spawn(move || loop {
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {
+                let input = input.trim();
+                if input.eq("hint") && exam::in_progress() {
+                    println!("{}", t!("exam.hint_locked"));
+                } else if input.eq("hint") {
+                    if let Some(hint) = &*failed_exercise_hint.lock().unwrap() {
+                        println!("{}", hint);
+                    }
+                } else if input.eq("doc") {
+                    match &*failed_exercise_notes.lock().unwrap() {
+                        Some(notes) => {
+                            let _ = doc::show(notes);
+                        }
+                        None => println!("{}", t!("watch.no_notes")),
+                    }
+                } else if input.eq("clear") {
+                    println!("\x1B[2J\x1B[1;1H");
+                } else if input.eq("quit") {
+                    println!("{}", t!("watch.bye"));
+                    std::process::exit(0);
+                } else {
+                    println!("{}", t!("watch.unknown", input));
+                }
+            }
+            Err(error) => println!("{}", t!("watch.read_error", error)),
+        }
+    });
+}
+
+pub fn watch(exercises: &[Exercise]) -> notify::Result<()> {
+    /* Clears the terminal with an ANSI escape code.
+    Works in UNIX and newer Windows terminals. */
+    fn clear_screen() {
+        println!("\x1Bc");
+    }
+
+    let (_watcher, rx) = start_watcher(Path::new("./exercises"))?;
+
+    clear_screen();
+
+    let to_owned_hint = |t: &Exercise| t.hint.text().to_owned();
+    // 找到未通过的练习在列表中的下标
+    let index_of = |t: &Exercise| {
+        exercises
+            .iter()
+            .position(|e| std::ptr::eq(e, t))
+            .expect("verify 返回的练习一定在列表中")
+    };
+
+    // 当前正在进行的练习, 只有它被修改时才重新检验
+    let mut current = match verify(exercises.iter()) {
+        Ok(_) => return Ok(()),
+        Err(exercise) => index_of(exercise),
+    };
+    // 开始做当前练习的时间, 通过练习时记下花费的时间
+    let mut started = Instant::now();
+    let failed_exercise_hint = Arc::new(Mutex::new(Some(to_owned_hint(&exercises[current]))));
+    let failed_exercise_notes = Arc::new(Mutex::new(exercises[current].notes.clone()));
+    spawn_watch_shell(&failed_exercise_hint, &failed_exercise_notes);
+    loop {
+        // 监视意外停止时练习并没有完成, 交给 main 报告错误
+        let changed = match next_changes(&rx, None) {
+            Some(changed) => changed,
+            None => return Err(notify::Error::Generic(t!("watch.stopped").to_string())),
+        };
+        // 不属于当前练习的文件 (包括其他练习和课程之外的文件) 一律忽略
+        if !changed.iter().any(|path| exercises[current].owns(path)) {
+            continue;
+        }
+        clear_screen();
+        match verify(exercises[current..].iter()) {
+            Ok(_) => {
+                stats::record(exercises[current].id(), started.elapsed().as_secs());
+                return Ok(());
+            }
+            Err(exercise) => {
+                if index_of(exercise) != current {
+                    stats::record(exercises[current].id(), started.elapsed().as_secs());
+                    started = Instant::now();
+                }
+                current = index_of(exercise);
+                let mut failed_exercise_hint = failed_exercise_hint.lock().unwrap();
+                *failed_exercise_hint = Some(to_owned_hint(exercise));
+                *failed_exercise_notes.lock().unwrap() = exercise.notes.clone();
+            }
+        }
+    }
+}
+
+// 阻塞到有文件被修改为止, 并把紧接着到来的事件合并、去重
+// 编辑器保存时常常先写临时文件再改名, 一次保存会产生好几个事件
+// 返回 None 表示监视已经停止; 指定了 wait 时, 等待这么久还没有文件被修改就返回空集合
+pub fn next_changes(
+    rx: &Receiver<DebouncedEvent>,
+    wait: Option<Duration>,
+) -> Option<HashSet<PathBuf>> {
+    let mut changed = HashSet::new();
+    loop {
+        let event = if !changed.is_empty() {
+            match rx.recv_timeout(COALESCE) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => return Some(changed),
+                Err(RecvTimeoutError::Disconnected) => return Some(changed),
+            }
+        } else if let Some(wait) = wait {
+            match rx.recv_timeout(wait) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => return Some(changed),
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        } else {
+            rx.recv().ok()?
+        };
+        match changed_path(event) {
+            Some(path) if !is_editor_temp(&path) => {
+                changed.insert(path);
+            }
+            _ => {}
+        }
+    }
+}
+
+// 取出事件中被修改后的文件路径
+fn changed_path(event: DebouncedEvent) -> Option<PathBuf> {
+    match event {
+        DebouncedEvent::Create(path)
+        | DebouncedEvent::Write(path)
+        | DebouncedEvent::Chmod(path)
+        | DebouncedEvent::Rename(_, path) => Some(path),
+        DebouncedEvent::Error(e, _) => {
+            println!("{}", t!("watch.error", format!("{:?}", e)));
+            None
+        }
+        _ => None,
+    }
+}
+
+// 判断是否是编辑器产生的交换文件或备份文件
+// 例如 vim 的 .xxx.c.swp 和 4913, emacs 的 .#xxx.c 和 #xxx.c#, 以及 xxx.c~
+fn is_editor_temp(path: &Path) -> bool {
+    let name = match path.file_name().and_then(|name| name.to_str()) {
+        Some(name) => name,
+        None => return true,
+    };
+    let swap_extensions = ["swp", "swo", "swx", "swn", "tmp", "bak"];
+    name == "4913"
+        || name.ends_with('~')
+        || name.starts_with(".#")
+        || (name.starts_with('#') && name.ends_with('#'))
+        || path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| swap_extensions.contains(&ext))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 测试编辑器的临时文件会被忽略, 练习文件不会
+    #[test]
+    fn test_is_editor_temp() {
+        assert!(is_editor_temp(Path::new("exercises/.int01.c.swp")));
+        assert!(is_editor_temp(Path::new("exercises/4913")));
+        assert!(is_editor_temp(Path::new("exercises/int01.c~")));
+        assert!(is_editor_temp(Path::new("exercises/.#int01.c")));
+        assert!(is_editor_temp(Path::new("exercises/#int01.c#")));
+        assert!(!is_editor_temp(Path::new("exercises/int01.c")));
+    }
+
+    // 测试同一次保存产生的多个事件被合并成一个去重的集合, 临时文件被忽略
+    #[test]
+    fn test_next_changes() {
+        let (tx, rx) = channel();
+        let source = PathBuf::from("exercises/int01.c");
+        tx.send(DebouncedEvent::Write(PathBuf::from("exercises/.int01.c.swp"))).unwrap();
+        tx.send(DebouncedEvent::Create(source.clone())).unwrap();
+        tx.send(DebouncedEvent::Rename(PathBuf::from("exercises/4913"), source.clone())).unwrap();
+        tx.send(DebouncedEvent::Write(source.clone())).unwrap();
+        tx.send(DebouncedEvent::NoticeWrite(source.clone())).unwrap();
+        let expected: HashSet<PathBuf> = vec![source.clone()].into_iter().collect();
+        assert_eq!(next_changes(&rx, None), Some(expected.clone()));
+
+        // 停止监视之前收到的修改仍然会返回, 之后返回 None
+        tx.send(DebouncedEvent::Write(source)).unwrap();
+        drop(tx);
+        assert_eq!(next_changes(&rx, None), Some(expected));
+        assert_eq!(next_changes(&rx, None), None);
+    }
+
+    // 测试指定了等待时间时, 没有修改返回空集合, 停止监视后返回 None
+    #[test]
+    fn test_next_changes_wait() {
+        let (tx, rx) = channel();
+        let wait = Some(Duration::from_millis(10));
+        assert_eq!(next_changes(&rx, wait), Some(HashSet::new()));
+        drop(tx);
+        assert_eq!(next_changes(&rx, wait), None);
+    }
+}